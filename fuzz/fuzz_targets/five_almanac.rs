@@ -0,0 +1,11 @@
+#![no_main]
+
+use aoc::five::{self, Almanac, Mapping};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = input.parse::<Mapping>();
+    if let Ok(almanac) = Almanac::from_lines(input.lines().map(String::from)) {
+        let _ = five::part_two(&almanac);
+    }
+});
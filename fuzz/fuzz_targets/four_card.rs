@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc::four::{Card, NumberSemantics};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|line: &str| {
+    let _ = Card::parse(line, NumberSemantics::Set);
+    let _ = Card::parse(line, NumberSemantics::Multiset);
+    let _ = Card::parse(line, NumberSemantics::Strict);
+});
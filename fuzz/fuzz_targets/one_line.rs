@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|line: &str| {
+    let _ = aoc::one::parse_line(line.to_string(), false);
+    let _ = aoc::one::parse_line(line.to_string(), true);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use aoc::seven::rules::{Rules, WildRank, CARDS};
+use aoc::seven::signature::{Classifier, Ranking};
+use aoc::seven::Hand;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|line: &str| {
+    let rules = Rules::new(CARDS, "J", WildRank::Low).unwrap();
+    let ranking = Ranking::Lexicographic;
+    let _ = Hand::from_str(line, &rules, &mut Classifier::new(&ranking));
+    let _ = Hand::from_poker_str(line, &rules);
+});
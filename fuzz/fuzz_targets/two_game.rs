@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc::two::{Game, GameConfiguration};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|line: &str| {
+    let _ = line.parse::<Game>();
+    let _ = line.parse::<GameConfiguration>();
+});
@@ -0,0 +1,302 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Result as AnyResult;
+
+use std::fmt;
+use std::fs;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+
+use clap::Args;
+
+use crate::executor::{self, Task};
+use crate::part::Part;
+use crate::{five, four, one, seven, three, two};
+
+#[derive(Args, Debug)]
+pub struct CommandAllArgs {
+    /// Directory holding an input named after each day, e.g. `one.txt`
+    #[clap(default_value = "inputs")]
+    pub inputs: String,
+
+    /// Use the sample inputs, e.g. `one_test.txt`
+    #[clap(long, action)]
+    pub sample: bool,
+
+    /// Parts to solve
+    #[clap(long, value_enum, default_value_t = Part::Both)]
+    pub part: Part,
+
+    /// Number of worker threads. Defaults to the number of CPUs.
+    #[clap(long, short)]
+    pub jobs: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Answer(String),
+    Failed(String),
+    Panicked(String),
+}
+
+/// What came of solving one part of one day
+#[derive(Debug, PartialEq)]
+pub struct Solution {
+    pub day: &'static str,
+    pub part: u8,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Day {}, part {}", self.day, self.part)?;
+        match &self.outcome {
+            Outcome::Answer(answer) => write!(f, ": {}", answer),
+            Outcome::Failed(error) => write!(f, " failed: {}", error),
+            Outcome::Panicked(message) => write!(f, " panicked: {}", message),
+        }
+    }
+}
+
+fn read_lines(file: &str) -> AnyResult<Vec<String>> {
+    let input = fs::read_to_string(file).map_err(|e| anyhow!("Could not read {}: {}", file, e))?;
+    Ok(input.lines().map(String::from).collect())
+}
+
+type Solver<M> = fn(&M) -> AnyResult<String>;
+
+/// Tasks solving the selected parts of a day. The input is parsed once, by
+/// whichever task gets to it first, and shared with the other part.
+fn day_tasks<M: Send + Sync + 'static>(
+    day: &'static str,
+    file: String,
+    part: Part,
+    parse: fn(&str) -> AnyResult<M>,
+    solvers: [Solver<M>; 2],
+) -> Vec<(&'static str, u8, Task<'static, AnyResult<String>>)> {
+    let model: Arc<OnceLock<Result<M, String>>> = Arc::new(OnceLock::new());
+    let parts: &[u8] = match part {
+        Part::One => &[1],
+        Part::Two => &[2],
+        Part::Both => &[1, 2],
+    };
+    parts
+        .iter()
+        .map(|&n| {
+            let model = model.clone();
+            let file = file.clone();
+            let solve = solvers[n as usize - 1];
+            let task: Task<AnyResult<String>> = Box::new(move || {
+                match model.get_or_init(|| parse(&file).map_err(|e| format!("{:#}", e))) {
+                    Ok(model) => solve(model),
+                    Err(error) => Err(anyhow!("{}", error)),
+                }
+            });
+            (day, n, task)
+        })
+        .collect()
+}
+
+/// Every implemented day, in order, reading the input of each day from
+/// `file(day)`
+fn tasks(
+    file: impl Fn(&str) -> String,
+    part: Part,
+) -> Vec<(&'static str, u8, Task<'static, AnyResult<String>>)> {
+    [
+        day_tasks::<Vec<String>>(
+            "one",
+            file("one"),
+            part,
+            read_lines,
+            [
+                |lines| Ok(one::part_one(lines).to_string()),
+                |lines| Ok(one::part_two(lines).to_string()),
+            ],
+        ),
+        day_tasks::<Vec<two::Game>>(
+            "two",
+            file("two"),
+            part,
+            |file| two::parse(read_lines(file)?.into_iter()).map_err(|e| anyhow!("{}", e)),
+            [
+                |games| Ok(two::part_one(games, &two::DEFAULT_BAG).to_string()),
+                |games| Ok(two::part_two(games)?.to_string()),
+            ],
+        ),
+        day_tasks::<three::Schematic>(
+            "three",
+            file("three"),
+            part,
+            |file| Ok(three::parse_tokens(read_lines(file)?.into_iter())?),
+            [
+                |(numbers, symbols)| Ok(three::part_one(numbers, symbols).to_string()),
+                |(numbers, symbols)| Ok(three::part_two(numbers, symbols)?.to_string()),
+            ],
+        ),
+        day_tasks::<Vec<four::Card>>(
+            "four",
+            file("four"),
+            part,
+            |file| {
+                let lines = read_lines(file)?.into_iter().map(Ok);
+                four::parse(lines, file, four::NumberSemantics::Set)
+            },
+            [
+                |cards| Ok(four::part_one(cards, &four::Scoring::Doubling)?.to_string()),
+                |cards| Ok(four::part_two(cards, four::PastEndPolicy::Error)?.to_string()),
+            ],
+        ),
+        day_tasks::<five::Almanac>(
+            "five",
+            file("five"),
+            part,
+            |file| five::Almanac::from_lines(read_lines(file)?.into_iter()),
+            [
+                |almanac| Ok(five::part_one(almanac)?.to_string()),
+                |almanac| Ok(five::part_two(almanac)?.to_string()),
+            ],
+        ),
+        day_tasks::<Vec<seven::Bid>>(
+            "seven",
+            file("seven"),
+            part,
+            |file| seven::parse(read_lines(file)?.into_iter()),
+            [
+                |bids| Ok(seven::part_one(bids)?.to_string()),
+                |bids| Ok(seven::part_two(bids)?.to_string()),
+            ],
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Answer to one part of a day, solved on its input in `inputs`
+pub fn answer(inputs: &str, day: &str, part: u8) -> AnyResult<String> {
+    answer_file(&format!("{}/{}.txt", inputs, day), day, part)
+}
+
+/// Answer to one part of a day, solved on `file`
+pub fn answer_file(file: &str, day: &str, part: u8) -> AnyResult<String> {
+    let (_, _, task) = tasks(|_| file.to_string(), Part::Both)
+        .into_iter()
+        .find(|(d, p, _)| *d == day && *p == part)
+        .ok_or_else(|| anyhow!("Day {} is not solved yet", day))?;
+    task()
+}
+
+/// Solves every day in parallel, in the order of the days and parts
+pub fn solve(args: &CommandAllArgs) -> Vec<Solution> {
+    let jobs = args.jobs.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+    let file = |day: &str| match args.sample {
+        true => format!("{}/{}_test.txt", args.inputs, day),
+        false => format!("{}/{}.txt", args.inputs, day),
+    };
+    let (labels, tasks): (Vec<(&str, u8)>, Vec<_>) = tasks(file, args.part)
+        .into_iter()
+        .map(|(day, part, task)| ((day, part), task))
+        .unzip();
+    labels
+        .into_iter()
+        .zip(executor::execute(tasks, jobs))
+        .map(|((day, part), result)| Solution {
+            day,
+            part,
+            outcome: match result {
+                Ok(Ok(answer)) => Outcome::Answer(answer),
+                Ok(Err(error)) => Outcome::Failed(format!("{:#}", error)),
+                Err(message) => Outcome::Panicked(message),
+            },
+        })
+        .collect()
+}
+
+pub fn run(args: &CommandAllArgs) -> AnyResult<Vec<Solution>> {
+    let solutions = solve(args);
+    for solution in solutions.iter() {
+        println!("{}", solution);
+    }
+    let failed = solutions
+        .iter()
+        .filter(|s| !matches!(s.outcome, Outcome::Answer(_)))
+        .count();
+    if failed > 0 {
+        bail!("{} of {} parts did not solve", failed, solutions.len());
+    }
+    Ok(solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(solutions: &[Solution]) -> Vec<String> {
+        solutions.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_samples() {
+        let args = CommandAllArgs {
+            inputs: "./inputs".to_string(),
+            sample: true,
+            part: Part::Both,
+            jobs: Some(4),
+        };
+        let expected = [
+            ("one", ["209", "281"]),
+            ("two", ["8", "2286"]),
+            ("three", ["4361", "467835"]),
+            ("four", ["13", "30"]),
+            ("five", ["35", "46"]),
+            ("seven", ["6440", "5905"]),
+        ];
+        let expected: Vec<String> = expected
+            .iter()
+            .flat_map(|(day, answers)| {
+                (1..)
+                    .zip(answers)
+                    .map(move |(part, answer)| format!("Day {}, part {}: {}", day, part, answer))
+            })
+            .collect();
+        let solutions = run(&args).unwrap();
+        assert_eq!(answers(&solutions), expected);
+
+        // the same answers in the same order with a single worker
+        let args = CommandAllArgs {
+            jobs: Some(1),
+            ..args
+        };
+        assert_eq!(answers(&solve(&args)), expected);
+    }
+
+    #[test]
+    fn test_answer() {
+        let sample = |day, part| answer_file(&format!("./inputs/{}_test.txt", day), day, part);
+        assert_eq!(sample("seven", 2).unwrap(), "5905");
+        assert_eq!(sample("three", 1).unwrap(), "4361");
+        assert!(sample("six", 1).is_err());
+        assert!(answer("./no-such-directory", "seven", 2).is_err());
+    }
+
+    #[test]
+    fn test_missing_inputs() {
+        let args = CommandAllArgs {
+            inputs: "./no-such-directory".to_string(),
+            sample: false,
+            part: Part::Two,
+            jobs: None,
+        };
+        let solutions = solve(&args);
+        assert_eq!(solutions.len(), 6);
+        assert!(solutions.iter().all(|s| s.part == 2));
+        assert!(matches!(&solutions[0].outcome,
+            Outcome::Failed(e) if e.starts_with("Could not read ./no-such-directory/one.txt")));
+        assert!(run(&args).is_err());
+    }
+}
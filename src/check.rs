@@ -0,0 +1,446 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Result as AnyResult;
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use clap::Args;
+use itertools::Itertools;
+
+use crate::day::Day;
+use crate::five::{self, Almanac};
+use crate::four::{self, Card, NumberSemantics, PastEndPolicy};
+use crate::generate::{generate, Rng};
+
+#[derive(Args, Debug)]
+pub struct CommandCheckArgs {
+    /// Day to check. Defaults to every day with a reference solver.
+    pub day: Option<Day>,
+
+    /// Number of generated inputs to try
+    #[clap(long, default_value_t = 100)]
+    pub cases: u64,
+
+    /// Size of each generated input, as for `generate`. Kept small since the
+    /// reference solvers are slow.
+    #[clap(long, short = 'n', default_value_t = 3)]
+    pub size: usize,
+
+    /// Seed of the first input, the following ones counting up from it
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+/// Number of scratchcards, playing out every copy one at a time
+pub fn four_part_two(cards: &[Card]) -> u64 {
+    let mut queue: VecDeque<usize> = (0..cards.len()).collect();
+    let mut count = 0;
+    while let Some(i) = queue.pop_front() {
+        count += 1;
+        let won = cards[i].number_matches() as usize;
+        queue.extend((i + 1..=i + won).filter(|&j| j < cards.len()));
+    }
+    count
+}
+
+/// Lowest location of any seed in the ranges, trying every seed
+pub fn five_part_two(almanac: &Almanac) -> AnyResult<u64> {
+    let ranges = almanac
+        .seeds
+        .iter()
+        .tuples()
+        .map(|(&start, &len)| match start.checked_add(len) {
+            Some(end) => Ok(start..end),
+            None => Err(anyhow!(
+                "Seed range runs past the largest seed: {} {}",
+                start,
+                len
+            )),
+        })
+        .collect::<AnyResult<Vec<_>>>()?;
+    ranges
+        .into_iter()
+        .flatten()
+        .map(|seed| almanac.location(seed))
+        .min()
+        .ok_or_else(|| anyhow!("No seeds found"))
+}
+
+/// An optimized solver and a naive one that should agree with it
+struct Check {
+    day: Day,
+    part: u8,
+    /// Random input of a size from a seed
+    generate: fn(usize, u64) -> String,
+    solve: fn(&str) -> AnyResult<u64>,
+    reference: fn(&str) -> AnyResult<u64>,
+    /// Smaller variants of an input, to look for a smaller disagreement
+    shrink: fn(&str) -> Vec<String>,
+}
+
+fn cards(input: &str) -> AnyResult<Vec<Card>> {
+    four::parse(
+        input.lines().map(|l| Ok(l.to_string())),
+        "-",
+        NumberSemantics::Set,
+    )
+}
+
+fn almanac(input: &str) -> AnyResult<Almanac> {
+    Almanac::from_lines(input.lines().map(String::from))
+}
+
+/// An almanac like the generated ones but with every number below 256, so
+/// that ranges often start or end at the same place and every seed can be
+/// tried. Seed ranges are long enough to be split by the maps.
+fn small_almanac(size: usize, seed: u64) -> String {
+    let span: u64 = 256;
+    let mut rng = Rng::new(seed);
+    let seeds = (0..size)
+        .flat_map(|_| [rng.below(span), rng.between(1, 64)])
+        .join(" ");
+    let names = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let maps = names
+        .iter()
+        .tuple_windows()
+        .map(|(from, to)| {
+            // source ranges that do not overlap, with gaps between some
+            let count = rng.between(2, 6);
+            let mut start = rng.below(span / count);
+            let mappings = (0..count)
+                .map(|_| {
+                    let len = rng.between(1, span / count);
+                    let mapping = format!("{} {} {}", rng.below(span), start, len);
+                    start += len;
+                    if rng.chance(0.3) {
+                        start += rng.below(span / count / 4);
+                    }
+                    mapping
+                })
+                .join("\n");
+            format!("{}-to-{} map:\n{}", from, to, mappings)
+        })
+        .join("\n\n");
+    format!("seeds: {}\n\n{}", seeds, maps)
+}
+
+/// Drops one card, numbering the others again
+fn shrink_cards(input: &str) -> Vec<String> {
+    let lines: Vec<&str> = input.lines().collect();
+    (0..lines.len())
+        .map(|skip| {
+            lines
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != skip)
+                .filter_map(|(_, l)| l.split_once(':'))
+                .enumerate()
+                .map(|(i, (_, numbers))| format!("Card {:>3}:{}", i + 1, numbers))
+                .join("\n")
+        })
+        .collect()
+}
+
+/// Drops a seed range or a mapping, or halves the length of a seed range
+fn shrink_almanac(input: &str) -> Vec<String> {
+    let lines: Vec<&str> = input.lines().collect();
+    let Some(seeds) = lines.first().and_then(|l| l.strip_prefix("seeds:")) else {
+        return vec![];
+    };
+    let pairs: Vec<(u64, u64)> = seeds
+        .split_whitespace()
+        .filter_map(|n| n.parse().ok())
+        .tuples()
+        .collect();
+    let with_seeds = |pairs: Vec<(u64, u64)>| {
+        let seeds = pairs.iter().map(|(s, l)| format!("{} {}", s, l)).join(" ");
+        format!("seeds: {}\n{}", seeds, lines[1..].join("\n"))
+    };
+    let mut variants = vec![];
+    for i in 0..pairs.len() {
+        let mut fewer = pairs.clone();
+        fewer.remove(i);
+        variants.push(with_seeds(fewer));
+    }
+    for i in 0..pairs.len() {
+        if pairs[i].1 > 1 {
+            let mut shorter = pairs.clone();
+            shorter[i].1 /= 2;
+            variants.push(with_seeds(shorter));
+        }
+    }
+    for (i, line) in lines.iter().enumerate().skip(1) {
+        if line.starts_with(|c: char| c.is_ascii_digit()) {
+            let mut fewer = lines.clone();
+            fewer.remove(i);
+            variants.push(fewer.join("\n"));
+        }
+    }
+    variants
+}
+
+fn checks() -> Vec<Check> {
+    vec![
+        Check {
+            day: Day::Four,
+            part: 2,
+            generate: |size, seed| generate(Day::Four, size, seed),
+            solve: |input| four::part_two(&cards(input)?, PastEndPolicy::Clamp),
+            reference: |input| Ok(four_part_two(&cards(input)?)),
+            shrink: shrink_cards,
+        },
+        Check {
+            day: Day::Five,
+            part: 2,
+            generate: small_almanac,
+            solve: |input| five::part_two(&almanac(input)?),
+            reference: |input| five_part_two(&almanac(input)?),
+            shrink: shrink_almanac,
+        },
+    ]
+}
+
+/// What a solver made of an input: its answer, or why it failed
+pub type Outcome = Result<u64, String>;
+
+/// An input on which the optimized and reference solvers give different
+/// answers, or only one of them fails
+#[derive(Debug)]
+pub struct Disagreement {
+    pub day: Day,
+    pub part: u8,
+    pub seed: u64,
+    pub input: String,
+    pub solved: Outcome,
+    pub expected: Outcome,
+}
+
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |outcome: &Outcome| match outcome {
+            Ok(answer) => answer.to_string(),
+            Err(error) => format!("error `{}`", error),
+        };
+        writeln!(
+            f,
+            "Day {:?}, part {} disagrees on seed {}: got {}, expected {}",
+            self.day,
+            self.part,
+            self.seed,
+            show(&self.solved),
+            show(&self.expected)
+        )?;
+        write!(f, "{}", self.input)
+    }
+}
+
+impl Check {
+    /// Both outcomes, when the solvers disagree on `input`. An input both
+    /// solvers fail on is not one they disagree on.
+    fn disagree(&self, input: &str) -> Option<(Outcome, Outcome)> {
+        let outcome = |result: AnyResult<u64>| result.map_err(|e| format!("{:#}", e));
+        let solved = outcome((self.solve)(input));
+        let expected = outcome((self.reference)(input));
+        match (&solved, &expected) {
+            (Ok(a), Ok(b)) if a == b => None,
+            (Err(_), Err(_)) => None,
+            _ => Some((solved, expected)),
+        }
+    }
+
+    /// Shrinks `input` for as long as the solvers keep disagreeing on it
+    fn minimize(&self, mut input: String) -> String {
+        while let Some(smaller) = (self.shrink)(&input)
+            .into_iter()
+            .find(|s| self.disagree(s).is_some())
+        {
+            input = smaller;
+        }
+        input
+    }
+
+    fn run(&self, seeds: std::ops::Range<u64>, size: usize) -> Option<Disagreement> {
+        seeds.into_iter().find_map(|seed| {
+            let input = (self.generate)(size, seed);
+            let _ = self.disagree(&input)?;
+            let input = self.minimize(input);
+            let (solved, expected) = self.disagree(&input)?;
+            Some(Disagreement {
+                day: self.day,
+                part: self.part,
+                seed,
+                input,
+                solved,
+                expected,
+            })
+        })
+    }
+}
+
+/// The first disagreement for each checked day, minimized
+pub fn check(args: &CommandCheckArgs) -> AnyResult<Vec<Disagreement>> {
+    let checks: Vec<Check> = checks()
+        .into_iter()
+        .filter(|c| args.day.is_none_or(|day| c.day == day))
+        .collect();
+    if checks.is_empty() {
+        bail!("No reference solver for day {:?}", args.day.unwrap());
+    }
+    Ok(checks
+        .iter()
+        .filter_map(|c| c.run(args.seed..args.seed + args.cases, args.size))
+        .collect())
+}
+
+pub fn run(args: &CommandCheckArgs) -> AnyResult<()> {
+    let disagreements = check(args)?;
+    for disagreement in disagreements.iter() {
+        println!("{}\n", disagreement);
+    }
+    if !disagreements.is_empty() {
+        bail!("{} solvers disagree", disagreements.len());
+    }
+    println!("No disagreement in {} cases", args.cases);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_samples() {
+        let input = std::fs::read_to_string("./inputs/four_test.txt").unwrap();
+        assert_eq!(four_part_two(&cards(&input).unwrap()), 30);
+        let input = std::fs::read_to_string("./inputs/five_test.txt").unwrap();
+        assert_eq!(five_part_two(&almanac(&input).unwrap()).unwrap(), 46);
+        let input = format!("seeds: {} 2", u64::MAX - 1);
+        assert!(five_part_two(&almanac(&input).unwrap()).is_err());
+    }
+
+    #[test]
+    fn solvers_agree() {
+        let args = CommandCheckArgs {
+            day: None,
+            cases: 50,
+            size: 4,
+            seed: 0,
+        };
+        let disagreements = check(&args).unwrap();
+        assert!(
+            disagreements.is_empty(),
+            "{}",
+            disagreements.iter().join("\n\n")
+        );
+        let args = CommandCheckArgs {
+            day: Some(Day::One),
+            ..args
+        };
+        assert!(check(&args).is_err());
+    }
+
+    #[test]
+    fn shrink_keeps_inputs_valid() {
+        let input = generate(Day::Four, 5, 1);
+        for smaller in shrink_cards(&input) {
+            assert_eq!(cards(&smaller).unwrap().len(), 4);
+        }
+        let input = small_almanac(3, 1);
+        for smaller in shrink_almanac(&input) {
+            almanac(&smaller).unwrap();
+        }
+        // the shrinker can halve a seed range, keeping all three
+        let seed_count = |input: &str| almanac(input).unwrap().seeds.len();
+        assert!(shrink_almanac(&input)
+            .iter()
+            .any(|smaller| smaller != &input
+                && seed_count(smaller) == 6
+                && smaller.lines().count() == input.lines().count()));
+    }
+
+    #[test]
+    fn small_almanacs_split_ranges() {
+        // a seed range longer than one seed runs across the edge of a mapping
+        // of the first map
+        let crosses = |input: &str| {
+            let almanac = almanac(input).unwrap();
+            let edges: Vec<u64> = almanac.collections[0]
+                .mappings
+                .iter()
+                .flat_map(|m| [m.src.start, m.src.end])
+                .collect();
+            almanac.seeds.iter().tuples().any(|(&start, &len)| {
+                len > 1 && edges.iter().any(|&e| start < e && e < start + len)
+            })
+        };
+        let count = (0..10)
+            .filter(|&seed| crosses(&small_almanac(3, seed)))
+            .count();
+        assert!(
+            count > 5,
+            "only {} of 10 almanacs split a seed range",
+            count
+        );
+    }
+
+    #[test]
+    fn minimizes_disagreement() {
+        // miscounts as soon as there are two cards
+        let check = Check {
+            day: Day::Four,
+            part: 2,
+            generate: |size, seed| generate(Day::Four, size, seed),
+            solve: |input| Ok(four_part_two(&cards(input)?).min(2)),
+            reference: |input| Ok(four_part_two(&cards(input)?)),
+            shrink: shrink_cards,
+        };
+        let disagreement = check.run(0..10, 20).unwrap();
+        assert_eq!(disagreement.seed, 0);
+        assert!(matches!(disagreement.expected, Ok(n) if n > 2));
+        assert!(disagreement.input.lines().count() <= 3);
+        assert!(disagreement.input.starts_with("Card   1:"));
+    }
+
+    #[test]
+    fn failures_disagree() {
+        // fails as soon as there are two cards
+        let check = Check {
+            day: Day::Four,
+            part: 2,
+            generate: |size, seed| generate(Day::Four, size, seed),
+            solve: |input| match cards(input)? {
+                cards if cards.len() > 1 => bail!("{} cards", cards.len()),
+                cards => Ok(four_part_two(&cards)),
+            },
+            reference: |input| Ok(four_part_two(&cards(input)?)),
+            shrink: shrink_cards,
+        };
+        let disagreement = check.run(0..10, 20).unwrap();
+        assert_eq!(disagreement.input.lines().count(), 2);
+        assert_eq!(disagreement.solved, Err("2 cards".to_string()));
+        assert!(disagreement.expected.is_ok());
+        assert!(disagreement
+            .to_string()
+            .starts_with("Day Four, part 2 disagrees on seed 0: got error `2 cards`, expected"));
+
+        // the other way round
+        let check = Check {
+            solve: check.reference,
+            reference: check.solve,
+            ..check
+        };
+        let disagreement = check.run(0..10, 20).unwrap();
+        assert!(disagreement.solved.is_ok());
+        assert!(disagreement.expected.is_err());
+    }
+}
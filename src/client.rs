@@ -0,0 +1,177 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Result as AnyResult;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+use clap::Args;
+use serde::Deserialize;
+
+pub const DEFAULT_URL: &str = "https://adventofcode.com/2023";
+
+/// Environment variable holding the session token
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+#[derive(Args, Debug)]
+pub struct ClientArgs {
+    /// Base URL of the puzzles. Defaults to the `url` of the config file, or
+    /// to the 2023 event.
+    #[clap(long)]
+    pub url: Option<String>,
+
+    /// JSON file with the `session` token and optionally the `url`. The
+    /// `AOC_SESSION` environment variable takes precedence over its token.
+    #[clap(long, default_value = ".aoc.json")]
+    pub config: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Config {
+    session: Option<String>,
+    url: Option<String>,
+}
+
+impl ClientArgs {
+    /// The config file, or an empty config when there is no such file
+    fn config(&self) -> AnyResult<Config> {
+        match fs::read_to_string(&self.config) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| anyhow!("Could not read {}: {}", self.config, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(anyhow!("Could not read {}: {}", self.config, e)),
+        }
+    }
+
+    /// A client for the configured endpoint and session
+    pub fn client(&self) -> AnyResult<Client> {
+        let config = self.config()?;
+        let session = env::var(SESSION_VAR)
+            .ok()
+            .or(config.session)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No session token: set {} or `session` in {}",
+                    SESSION_VAR,
+                    self.config
+                )
+            })?;
+        let url = self
+            .url
+            .clone()
+            .or(config.url)
+            .unwrap_or_else(|| DEFAULT_URL.to_string());
+        Ok(Client::new(&url, session.trim()))
+    }
+}
+
+/// Talks to the puzzle site as a logged in user
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str) -> Self {
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent
+            .request(method, &format!("{}{}", self.base_url, path))
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", concat!("aoc/", env!("CARGO_PKG_VERSION")))
+    }
+
+    fn body(path: &str, response: Result<ureq::Response, ureq::Error>) -> AnyResult<String> {
+        match response {
+            Ok(response) => Ok(response.into_string()?),
+            Err(ureq::Error::Status(code, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                bail!("{} answered {}: {}", path, code, body.trim())
+            }
+            Err(e) => bail!("Could not reach {}: {}", path, e),
+        }
+    }
+
+    /// Body of the page at `path`, e.g. `/day/1/input`
+    pub fn get(&self, path: &str) -> AnyResult<String> {
+        Client::body(path, self.request("GET", path).call())
+    }
+
+    /// Body of the answer to posting `form` to `path`
+    pub fn post(&self, path: &str, form: &[(&str, &str)]) -> AnyResult<String> {
+        Client::body(path, self.request("POST", path).send_form(form))
+    }
+}
+
+/// A local HTTP server answering with canned responses, for tests
+#[cfg(test)]
+pub mod stub {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    /// A request as the stub saw it
+    #[derive(Debug, Default)]
+    pub struct Request {
+        pub line: String,
+        pub cookie: Option<String>,
+        pub body: String,
+    }
+
+    /// Serves one request for each `(status, body)`, in order, then returns
+    /// the requests it saw. Also returns the base URL to reach it.
+    pub fn serve(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<Request>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|(status, body)| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream);
+                    let mut request = Request::default();
+                    let mut length = 0;
+                    reader.read_line(&mut request.line).unwrap();
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        let header = header.trim();
+                        if header.is_empty() {
+                            break;
+                        }
+                        let (name, value) = header.split_once(": ").unwrap();
+                        match name.to_lowercase().as_str() {
+                            "cookie" => request.cookie = Some(value.to_string()),
+                            "content-length" => length = value.parse().unwrap(),
+                            _ => {}
+                        }
+                    }
+                    let mut content = vec![0; length];
+                    reader.read_exact(&mut content).unwrap();
+                    request.body = String::from_utf8(content).unwrap();
+                    write!(
+                        reader.get_mut(),
+                        "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                    request
+                })
+                .collect()
+        });
+        (url, handle)
+    }
+}
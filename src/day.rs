@@ -0,0 +1,25 @@
+use clap::ValueEnum;
+
+/// A day of the event, as named on the command line
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Day {
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+}
+
+impl Day {
+    /// Number of the day in the event
+    pub fn number(self) -> u32 {
+        self as u32 + 1
+    }
+
+    /// Name of the day, as used for its input file, e.g. `seven`
+    pub fn name(self) -> String {
+        self.to_possible_value().unwrap().get_name().to_string()
+    }
+}
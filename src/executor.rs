@@ -0,0 +1,99 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+pub type Task<'a, T> = Box<dyn FnOnce() -> T + Send + 'a>;
+
+/// Message of a caught panic, when it has one
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// Runs every task on a pool of `jobs` worker threads. Results come back in
+/// the order the tasks were given, whatever order they finish in. A task
+/// that panics gives the panic message instead of a result, and does not
+/// stop the others.
+pub fn execute<T: Send>(tasks: Vec<Task<'_, T>>, jobs: usize) -> Vec<Result<T, String>> {
+    let count = tasks.len();
+    let tasks: Vec<Mutex<Option<Task<'_, T>>>> =
+        tasks.into_iter().map(|t| Mutex::new(Some(t))).collect();
+    let results: Vec<Mutex<Option<Result<T, String>>>> =
+        (0..count).map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, count.max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= count {
+                    break;
+                }
+                let task = tasks[i].lock().unwrap().take().unwrap();
+                let result = panic::catch_unwind(AssertUnwindSafe(task)).map_err(panic_message);
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|r| r.into_inner().unwrap().unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn results_keep_task_order() {
+        for jobs in [0, 1, 3, 16] {
+            let tasks: Vec<Task<usize>> = (0..10usize)
+                .map(|i| {
+                    Box::new(move || {
+                        // later tasks finish first
+                        thread::sleep(Duration::from_millis(10 - i as u64));
+                        i * i
+                    }) as Task<usize>
+                })
+                .collect();
+            let results = execute(tasks, jobs);
+            assert_eq!(
+                results,
+                (0..10).map(|i| Ok(i * i)).collect::<Vec<_>>(),
+                "{} jobs",
+                jobs
+            );
+        }
+        assert!(execute(Vec::<Task<()>>::new(), 4).is_empty());
+    }
+
+    #[test]
+    fn panics_are_captured() {
+        let tasks: Vec<Task<u32>> = vec![
+            Box::new(|| 1),
+            Box::new(|| panic!("solver {} failed", 2)),
+            Box::new(|| panic!("no input")),
+            Box::new(|| 4),
+        ];
+        assert_eq!(
+            execute(tasks, 2),
+            vec![
+                Ok(1),
+                Err("solver 2 failed".to_string()),
+                Err("no input".to_string()),
+                Ok(4)
+            ]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::anyhow;
+use anyhow::Result as AnyResult;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use crate::client::{Client, ClientArgs};
+use crate::day::Day;
+
+#[derive(Args, Debug)]
+pub struct CommandFetchArgs {
+    pub day: Day,
+
+    /// Directory to keep the inputs in, named after their day, e.g. `one.txt`
+    #[clap(long, default_value = "inputs")]
+    pub inputs: String,
+
+    #[clap(flatten)]
+    pub client: ClientArgs,
+}
+
+/// Where the input of `day` is kept
+pub fn input_path(inputs: &str, day: Day) -> PathBuf {
+    Path::new(inputs).join(format!("{}.txt", day.name()))
+}
+
+/// Downloads the input of `day` unless it is already in `inputs`, and returns
+/// its path. `client` is only created when there is something to download.
+pub fn fetch(
+    inputs: &str,
+    day: Day,
+    client: impl FnOnce() -> AnyResult<Client>,
+) -> AnyResult<(PathBuf, bool)> {
+    let path = input_path(inputs, day);
+    if path.exists() {
+        return Ok((path, false));
+    }
+    let input = client()?.get(&format!("/day/{}/input", day.number()))?;
+    fs::create_dir_all(inputs).map_err(|e| anyhow!("Could not create {}: {}", inputs, e))?;
+    // a partial write must not be mistaken for a cached input
+    let partial = path.with_extension("txt.part");
+    fs::write(&partial, input)
+        .and_then(|_| fs::rename(&partial, &path))
+        .map_err(|e| anyhow!("Could not write {}: {}", path.display(), e))?;
+    Ok((path, true))
+}
+
+pub fn run(args: &CommandFetchArgs) -> AnyResult<PathBuf> {
+    let (path, downloaded) = fetch(&args.inputs, args.day, || args.client.client())?;
+    match downloaded {
+        true => println!("Saved {}", path.display()),
+        false => println!("Using cached {}", path.display()),
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    use crate::client::stub;
+
+    fn scratch_dir(name: &str) -> String {
+        let dir = env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn downloads_once() {
+        let inputs = scratch_dir("fetch");
+        let (url, server) = stub::serve(vec![(200, "32T3K 765\n")]);
+        let client = || Ok(Client::new(&url, "secret"));
+
+        let (path, downloaded) = fetch(&inputs, Day::Seven, client).unwrap();
+        assert!(downloaded);
+        assert_eq!(path, Path::new(&inputs).join("seven.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "32T3K 765\n");
+
+        // the stub only answers once, and the session is not even needed
+        let (_, downloaded) = fetch(&inputs, Day::Seven, || panic!("no client")).unwrap();
+        assert!(!downloaded);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].line.trim(), "GET /day/7/input HTTP/1.1");
+        assert_eq!(requests[0].cookie.as_deref(), Some("session=secret"));
+        fs::remove_dir_all(&inputs).unwrap();
+    }
+
+    #[test]
+    fn failed_download_is_not_cached() {
+        let inputs = scratch_dir("fetch-failed");
+        let (url, server) = stub::serve(vec![(400, "Please log in")]);
+        let error = fetch(&inputs, Day::One, || Ok(Client::new(&url, "expired"))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "/day/1/input answered 400: Please log in"
+        );
+        assert!(!input_path(&inputs, Day::One).exists());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn session_from_config() {
+        let inputs = scratch_dir("fetch-config");
+        fs::create_dir_all(&inputs).unwrap();
+        let config = Path::new(&inputs).join("aoc.json");
+        let args = ClientArgs {
+            url: None,
+            config: config.to_string_lossy().to_string(),
+        };
+        if env::var(crate::client::SESSION_VAR).is_err() {
+            assert!(args.client().is_err());
+        }
+        fs::write(
+            &config,
+            r#"{"session": "abc", "url": "http://localhost:1"}"#,
+        )
+        .unwrap();
+        assert!(args.client().is_ok());
+        fs::write(&config, "session=abc").unwrap();
+        assert!(args.client().is_err());
+
+        // a config that exists but cannot be read is not taken as no config
+        let args = ClientArgs {
+            url: None,
+            config: inputs.clone(),
+        };
+        let error = args.client().err().unwrap().to_string();
+        assert!(
+            error.starts_with(&format!("Could not read {}: ", inputs)),
+            "{}",
+            error
+        );
+        fs::remove_dir_all(&inputs).unwrap();
+    }
+}
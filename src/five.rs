@@ -1,3 +1,4 @@
+use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Error as AnyError;
 use anyhow::Result as AnyResult;
@@ -10,41 +11,62 @@ use std::vec;
 
 use clap::Args;
 
+use crate::parallel;
+use crate::part::{Answers, Part};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 #[derive(Args, Debug)]
 pub struct CommandFiveArgs {
-    file: String,
+    pub file: String,
+
+    /// Parts to solve
+    #[clap(long, value_enum, default_value_t = Part::Both)]
+    pub part: Part,
+
+    /// Worker threads for the parallel loops. Needs the `parallel` feature,
+    /// and defaults to the number of CPUs.
+    #[clap(long)]
+    pub threads: Option<usize>,
+}
 
-    #[clap(long, short = '2', action)]
-    two: bool,
+/// The seeds to plant and the maps from seeds to locations, in order
+#[derive(Debug)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub collections: Vec<MappingCollection>,
 }
 
 #[derive(Debug)]
-struct MappingCollection {
-    mappings: Vec<Mapping>,
+pub struct MappingCollection {
+    pub mappings: Vec<Mapping>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-struct Range {
-    start: u64,
-    end: u64,
+pub struct Range {
+    pub start: u64,
+    pub end: u64,
 }
 
 impl Range {
-    fn intersection(&self, other: &Self) -> Option<Range> {
-        if self.end < other.start || other.end < self.start {
-            return None;
-        }
-        Some(Range {
-            start: std::cmp::max(self.start, other.start),
-            end: std::cmp::min(self.end, other.end),
-        })
+    /// The seeds in both ranges, if there are any. Ranges exclude their end.
+    pub fn intersection(&self, other: &Self) -> Option<Range> {
+        let start = std::cmp::max(self.start, other.start);
+        let end = std::cmp::min(self.end, other.end);
+        (start < end).then_some(Range { start, end })
     }
 
-    fn len(&self) -> u64 {
+    pub fn len(&self) -> u64 {
         self.end - self.start
     }
 
-    fn subtract(&self, other: &Self) -> Vec<Range> {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The non-empty pieces of this range outside of `other`, in order
+    pub fn subtract(&self, other: &Self) -> Vec<Range> {
         let mut results = vec![];
         if self == other {
             return results;
@@ -62,8 +84,8 @@ impl Range {
                     end: self.end,
                 })
             }
-        } else {
-            results.push(self.clone());
+        } else if !self.is_empty() {
+            results.push(*self);
         }
         // println!("SUBTRACT\n\n{:#?} - {:#?} = {:#?}\n\n", self, other, results);
         results
@@ -71,30 +93,30 @@ impl Range {
 }
 
 impl MappingCollection {
-    fn from_lines<T: Iterator<Item = String>>(iter: &mut T) -> AnyResult<Self> {
+    pub fn from_lines<T: Iterator<Item = String>>(iter: &mut T) -> AnyResult<Self> {
         let mut mappings: Vec<Mapping> = vec![];
-        while let Some(line) = iter.next() {
-            if line.trim().len() == 0 || line.contains("map") {
+        for line in iter.by_ref() {
+            if line.trim().is_empty() || line.contains("map") {
                 break;
             }
-            mappings.push(line.as_str().parse::<Mapping>().unwrap());
+            mappings.push(line.as_str().parse::<Mapping>()?);
         }
-        if mappings.len() == 0 {
+        if mappings.is_empty() {
             bail!("No mappings found");
         }
-        Ok(MappingCollection { mappings: mappings })
+        Ok(MappingCollection { mappings })
     }
 
-    fn map(&self, elem: u64) -> u64 {
+    pub fn map(&self, elem: u64) -> u64 {
         for mapping in self.mappings.iter() {
             if elem >= mapping.src.start && elem < mapping.src.end {
                 return mapping.dst.start + (elem - mapping.src.start);
             }
         }
-        return elem;
+        elem
     }
 
-    fn map_range(&self, range: Range) -> Vec<Range> {
+    pub fn map_range(&self, range: Range) -> Vec<Range> {
         let mut remainders = vec![range];
         let mut mapped = vec![];
         for mapping in self.mappings.iter() {
@@ -108,7 +130,7 @@ impl MappingCollection {
                         let start =
                             mapping.dst.start + (intersection_range.start - mapping.src.start);
                         mapped.push(Range {
-                            start: start,
+                            start,
                             end: start + intersection_range.len(),
                         });
 
@@ -126,15 +148,15 @@ impl MappingCollection {
             }
             remainders = new_remainders.clone();
         }
-        mapped.extend(remainders.into_iter());
+        mapped.extend(remainders);
         mapped
     }
 }
 
 #[derive(Debug)]
-struct Mapping {
-    src: Range,
-    dst: Range,
+pub struct Mapping {
+    pub src: Range,
+    pub dst: Range,
 }
 
 impl std::str::FromStr for Mapping {
@@ -143,83 +165,133 @@ impl std::str::FromStr for Mapping {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let vals = s
             .split(" ")
-            .into_iter()
             .map(|i| i.parse::<u64>().map_err(|e| e.into()))
             .collect::<Result<Vec<u64>, AnyError>>()?;
-        if vals.len() != 3 {
+        let &[dst, src, len] = &vals[..] else {
             bail!("Could not parse mapping line: {}", s);
-        }
+        };
+        let end = |start: u64| {
+            start
+                .checked_add(len)
+                .ok_or_else(|| anyhow!("Mapping runs past the largest seed: {}", s))
+        };
         Ok(Mapping {
             src: Range {
-                start: vals[1],
-                end: vals[1] + vals[2],
+                start: src,
+                end: end(src)?,
             },
             dst: Range {
-                start: vals[0],
-                end: vals[0] + vals[2],
+                start: dst,
+                end: end(dst)?,
             },
         })
     }
 }
 
-pub fn run(args: &CommandFiveArgs) -> AnyResult<u64> {
-    let file = File::open(args.file.as_str()).expect("Should have been able to read the file");
-    let mut lines = io::BufReader::new(file)
-        .lines()
-        .filter_map(|s| s.ok())
-        .into_iter();
-    let mut collections: Vec<MappingCollection> = vec![];
-    let mut seeds: Vec<u64> = vec![];
-    while let Some(line) = lines.next() {
-        if line.starts_with("seeds:") {
-            seeds = line[6..]
-                .split_whitespace()
-                .map(|n| n.trim().parse::<u64>())
-                .collect::<Result<Vec<u64>, ParseIntError>>()?;
-        } else if line.contains("map") {
-            collections.push(MappingCollection::from_lines(&mut lines).unwrap());
+impl Almanac {
+    pub fn from_lines<T: Iterator<Item = String>>(mut lines: T) -> AnyResult<Self> {
+        let mut collections: Vec<MappingCollection> = vec![];
+        let mut seeds: Vec<u64> = vec![];
+        while let Some(line) = lines.next() {
+            if let Some(seed_list) = line.strip_prefix("seeds:") {
+                seeds = seed_list
+                    .split_whitespace()
+                    .map(|n| n.trim().parse::<u64>())
+                    .collect::<Result<Vec<u64>, ParseIntError>>()?;
+            } else if line.contains("map") {
+                collections.push(MappingCollection::from_lines(&mut lines)?);
+            }
         }
+        if seeds.is_empty() {
+            bail!("No seeds found");
+        }
+        Ok(Almanac { seeds, collections })
     }
-    if !args.two {
-        let result = seeds
+
+    /// Where a seed ends up once every map has been applied
+    pub fn location(&self, seed: u64) -> u64 {
+        self.collections
             .iter()
-            .map(|&s| {
-                return collections
-                    .iter()
-                    .fold(s, |curr, collection| collection.map(curr));
-            })
-            .min()
-            .unwrap();
-        println!("{}", result);
-        Ok(result)
-    } else {
-        let mut ranges: Vec<Range> = vec![];
-        let mut seed_iter = seeds.into_iter();
-        while let Some(seed) = seed_iter.next() {
-            ranges.push(Range {
-                start: seed,
-                end: seed + seed_iter.next().unwrap() - 1,
+            .fold(seed, |curr, collection| collection.map(curr))
+    }
+
+    /// Where the seeds of a range end up once every map has been applied
+    pub fn locations(&self, range: Range) -> Vec<Range> {
+        self.collections
+            .iter()
+            .fold(vec![range], |ranges, collection| {
+                ranges
+                    .into_iter()
+                    .flat_map(|r| collection.map_range(r))
+                    .collect()
             })
-        }
-        for collection in collections.iter() {
-            let mut new_ranges: Vec<Range> = vec![];
-            for range in ranges {
-                for r in collection.map_range(range) {
-                    new_ranges.push(r);
-                }
-            }
-            ranges = new_ranges.clone();
-        }
-        let result = ranges.iter().map(|r| r.start).min().unwrap();
-        println!("{}", result);
-        Ok(result)
     }
 }
 
+/// Lowest location of any of the seeds
+pub fn part_one(almanac: &Almanac) -> AnyResult<u64> {
+    #[cfg(feature = "parallel")]
+    let seeds = almanac.seeds.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let seeds = almanac.seeds.iter();
+    seeds
+        .map(|&s| almanac.location(s))
+        .min()
+        .ok_or_else(|| anyhow!("No seeds found"))
+}
+
+/// Lowest location of any seed in the ranges given by pairs of start and
+/// length
+pub fn part_two(almanac: &Almanac) -> AnyResult<u64> {
+    let mut ranges: Vec<Range> = vec![];
+    for pair in almanac.seeds.chunks(2) {
+        let &[seed, len] = pair else {
+            bail!("Seed {} has no range length", pair[0]);
+        };
+        ranges.push(Range {
+            start: seed,
+            end: seed.checked_add(len).ok_or_else(|| {
+                anyhow!("Seed range runs past the largest seed: {} {}", seed, len)
+            })?,
+        })
+    }
+    // each range is mapped on its own, so they can be spread over threads
+    #[cfg(feature = "parallel")]
+    let ranges = ranges.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let ranges = ranges.iter();
+    ranges
+        .filter_map(|&range| almanac.locations(range).iter().map(|r| r.start).min())
+        .min()
+        .ok_or_else(|| anyhow!("No seeds found"))
+}
+
+pub fn run(args: &CommandFiveArgs) -> AnyResult<Answers<u64>> {
+    let file =
+        File::open(&args.file).map_err(|e| anyhow!("Could not read {}: {}", args.file, e))?;
+    let lines = io::BufReader::new(file)
+        .lines()
+        .collect::<io::Result<Vec<String>>>()?;
+    let almanac = Almanac::from_lines(lines.into_iter())?;
+    let answers = parallel::install(args.threads, || {
+        Answers::solve(args.part, || part_one(&almanac), || part_two(&almanac))
+    })??;
+    println!("{}", answers);
+    Ok(answers)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::time::Instant;
+
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
+    use crate::day::Day;
+    use crate::generate::generate;
+
     #[test]
     fn test_parse() {
         let input = "seeds: 79 14 55 13
@@ -233,33 +305,158 @@ soil-to-fertilizer map:
 37 52 2
 39 0 15
 ";
-        let mut lines = input.split("\n").map(|l| l.to_string()).into_iter();
-        let mut collections: Vec<MappingCollection> = vec![];
-        while let Some(line) = lines.next() {
-            if line.contains("map") {
-                collections.push(MappingCollection::from_lines(&mut lines).unwrap());
-            }
-        }
+        let almanac = Almanac::from_lines(input.split("\n").map(|l| l.to_string())).unwrap();
+        assert_eq!(vec![79, 14, 55, 13], almanac.seeds);
+        let collections = almanac.collections;
         assert_eq!(2, collections.len());
         assert_eq!(2, collections[0].mappings.len());
         assert_eq!(3, collections[1].mappings.len());
+
+        let almanac = Almanac::from_lines(input.replace(" 13", "").lines().map(|l| l.to_string()));
+        assert!(part_two(&almanac.unwrap()).is_err());
+        assert!(
+            Almanac::from_lines(["seed-to-soil map:", "1 2"].iter().map(|l| l.to_string()))
+                .is_err()
+        );
+        assert!(format!("0 {} 2", u64::MAX).parse::<Mapping>().is_err());
+    }
+
+    /// Seeds of a range below 64, one bit each
+    fn bits(range: &Range) -> u64 {
+        (range.start..range.end).fold(0, |bits, seed| bits | 1 << seed)
+    }
+
+    /// Ranges below 64, empty ones included
+    fn small_range() -> impl Strategy<Value = Range> {
+        (0u64..64).prop_flat_map(|start| (start..=64).prop_map(move |end| Range { start, end }))
+    }
+
+    proptest! {
+        #[test]
+        fn intersection_is_symmetric(a in small_range(), b in small_range()) {
+            prop_assert_eq!(a.intersection(&b), b.intersection(&a));
+        }
+
+        #[test]
+        fn intersection_holds_common_seeds(a in small_range(), b in small_range()) {
+            let inter = a.intersection(&b);
+            prop_assert_eq!(inter.as_ref().map_or(0, bits), bits(&a) & bits(&b));
+            prop_assert!(inter.is_none_or(|i| !i.is_empty()), "empty {:?}", inter);
+        }
+
+        #[test]
+        fn subtract_holds_seeds_outside(a in small_range(), b in small_range()) {
+            let pieces = a.subtract(&b);
+            let covered = pieces.iter().fold(0, |all, p| all | bits(p));
+            prop_assert_eq!(covered, bits(&a) & !bits(&b), "pieces {:?}", pieces);
+        }
+
+        #[test]
+        fn subtract_pieces_are_disjoint(a in small_range(), b in small_range()) {
+            let pieces = a.subtract(&b);
+            let inter = a.intersection(&b).as_ref().map_or(0, bits);
+            prop_assert!(
+                pieces.iter().map(bits).chain([inter]).tuple_combinations().all(|(p, q)| p & q == 0),
+                "pieces {:?}",
+                pieces
+            );
+        }
+
+        #[test]
+        fn subtract_pieces_are_ordered(a in small_range(), b in small_range()) {
+            let pieces = a.subtract(&b);
+            prop_assert!(pieces.iter().all(|p| !p.is_empty()), "pieces {:?}", pieces);
+            prop_assert!(pieces.windows(2).all(|w| w[0].end <= w[1].start), "pieces {:?}", pieces);
+        }
+
+        #[test]
+        fn subtract_keeps_length(a in small_range(), b in small_range()) {
+            let pieces = a.subtract(&b);
+            let inter = a.intersection(&b);
+            prop_assert_eq!(
+                pieces.iter().map(Range::len).sum::<u64>() + inter.map_or(0, |i| i.len()),
+                a.len(),
+                "pieces {:?}, intersection {:?}",
+                pieces,
+                inter
+            );
+        }
+    }
+
+    #[test]
+    fn test_range_edges() {
+        // a seed range starting where a mapping ends is not mapped by it
+        let input = "seeds: 81 1\n\nseed-to-soil map:\n33 72 9";
+        let almanac = Almanac::from_lines(input.lines().map(String::from)).unwrap();
+        assert_eq!(part_two(&almanac).unwrap(), 81);
+
+        // the last seed of a range counts
+        let input = "seeds: 10 5\n\nseed-to-soil map:\n0 14 1";
+        let almanac = Almanac::from_lines(input.lines().map(String::from)).unwrap();
+        assert_eq!(part_two(&almanac).unwrap(), 0);
+
+        // a range may end on the largest seed, but not run past it
+        let input = format!("seeds: {} 1", u64::MAX - 1);
+        let almanac = Almanac::from_lines(input.lines().map(String::from)).unwrap();
+        assert_eq!(part_two(&almanac).unwrap(), u64::MAX - 1);
+        let input = format!("seeds: {} 2", u64::MAX - 1);
+        let almanac = Almanac::from_lines(input.lines().map(String::from)).unwrap();
+        assert!(part_two(&almanac).is_err());
     }
 
     #[test]
     fn test_input() {
         let r = self::run(&CommandFiveArgs {
             file: "./inputs/five_test.txt".to_string(),
-            two: false,
+            part: Part::One,
+            threads: None,
         });
-        assert_eq!(r.unwrap(), 35,);
+        assert_eq!(r.unwrap().one, Some(35));
     }
 
     #[test]
     fn test_input_part_two() {
         let r = self::run(&CommandFiveArgs {
             file: "./inputs/five_test.txt".to_string(),
-            two: true,
+            part: Part::Two,
+            threads: None,
         });
-        assert_eq!(r.unwrap(), 46,);
+        assert_eq!(r.unwrap().two, Some(46));
+    }
+
+    #[test]
+    fn test_input_both_parts() {
+        let r = self::run(&CommandFiveArgs {
+            file: "./inputs/five_test.txt".to_string(),
+            part: Part::Both,
+            threads: None,
+        });
+        assert_eq!(
+            r.unwrap(),
+            Answers {
+                one: Some(35),
+                two: Some(46)
+            }
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_parallel() {
+        let input = generate(Day::Five, 50_000, 0);
+        let almanac = Almanac::from_lines(input.lines().map(String::from)).unwrap();
+        let mut answers = vec![];
+        for threads in parallel::bench_threads() {
+            let start = Instant::now();
+            let one = parallel::install(threads, || part_one(&almanac).unwrap()).unwrap();
+            println!("part one, {:?} threads: {:?}", threads, start.elapsed());
+
+            let start = Instant::now();
+            let two = parallel::install(threads, || part_two(&almanac).unwrap()).unwrap();
+            println!("part two, {:?} threads: {:?}", threads, start.elapsed());
+
+            answers.push((one, two));
+        }
+        assert!(answers.iter().all_equal());
     }
 }
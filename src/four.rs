@@ -1,100 +1,477 @@
+use anyhow::anyhow;
 use anyhow::Error as AnyError;
 use anyhow::Result as AnyResult;
 use thiserror::Error;
 
-use std::collections::VecDeque;
 use std::{
+    collections::HashSet,
+    fmt,
     fs::File,
     io::{self, BufRead},
+    num::ParseIntError,
+    ops::Range,
     str::FromStr,
 };
 
-use clap::Args;
+use clap::{Args, ValueEnum};
+use itertools::Itertools;
+
+use crate::part::{Answers, Part};
+
+pub mod diagnostic;
+
+use diagnostic::CardDiagnostic;
 
 #[derive(Args, Debug)]
 pub struct CommandFourArgs {
-    file: String,
+    pub file: String,
+
+    /// Parts to solve
+    #[clap(long, value_enum, default_value_t = Part::Both)]
+    pub part: Part,
+
+    /// What to do when a card wins copies of cards past the end of the table
+    #[clap(long, value_enum, default_value_t = PastEndPolicy::Error)]
+    pub past_end: PastEndPolicy,
+
+    /// How to treat numbers repeated within a card. Repeats are reported as
+    /// a warning, or as an error with `strict`.
+    #[clap(long, value_enum, default_value_t = NumberSemantics::Set)]
+    pub numbers: NumberSemantics,
+
+    /// Part one scoring: `doubling`, `linear`, `fibonacci` or a table of
+    /// scores by number of matches, e.g. `table:1,3,9`
+    #[clap(long, default_value = "doubling")]
+    pub scoring: Scoring,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum PastEndPolicy {
+    /// Ignore wins that run off the end of the table
+    Clamp,
+    /// Fail with an error
+    Error,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum NumberSemantics {
+    /// A number repeated on one side of a card counts once
+    Set,
+    /// A number drawn several times counts as a match every time
+    Multiset,
+    /// Numbers may only appear once on each side of a card
+    Strict,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scoring {
+    /// 1, 2, 4, 8, ...
+    Doubling,
+    /// 1, 2, 3, 4, ...
+    Linear,
+    /// 1, 1, 2, 3, 5, ...
+    Fibonacci,
+    /// `table[n - 1]` for `n` matches
+    Table(Vec<u128>),
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            "fibonacci" => Ok(Scoring::Fibonacci),
+            _ => match s.strip_prefix("table:") {
+                Some(table) => table
+                    .split(',')
+                    .map(|n| n.trim().parse::<u128>())
+                    .collect::<Result<Vec<u128>, _>>()
+                    .map(Scoring::Table)
+                    .map_err(|e| format!("Invalid score table `{}`: {}", table, e)),
+                None => Err(format!("Unknown scoring scheme `{}`", s)),
+            },
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ScoreError {
+    #[error("Score for {0} matches does not fit in 128 bits")]
+    Overflow(u32),
+
+    #[error("Score table has no entry for {0} matches")]
+    MissingTableEntry(u32),
+}
 
-    #[clap(long, short = '2', action)]
-    two: bool,
+impl Scoring {
+    pub fn score(&self, matches: u32) -> Result<u128, ScoreError> {
+        if matches == 0 {
+            return Ok(0);
+        }
+        match self {
+            Scoring::Doubling => 2u128
+                .checked_pow(matches - 1)
+                .ok_or(ScoreError::Overflow(matches)),
+            Scoring::Linear => Ok(matches as u128),
+            Scoring::Fibonacci => {
+                let (mut a, mut b) = (0u128, 1u128);
+                for _ in 1..matches {
+                    (a, b) = (b, a.checked_add(b).ok_or(ScoreError::Overflow(matches))?);
+                }
+                Ok(b)
+            }
+            Scoring::Table(table) => table
+                .get(matches as usize - 1)
+                .copied()
+                .ok_or(ScoreError::MissingTableEntry(matches)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    pub number: u32,
+    pub winning: HashSet<i32>,
+    pub draw: Vec<i32>,
+    /// How many numbers repeat one before them on the same side
+    pub repeated: usize,
 }
 
-#[derive(Debug, PartialEq)]
-struct Card {
-    number: u32,
-    winning: Vec<i32>,
-    draw: Vec<i32>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CardField {
+    Id,
+    Winning,
+    Draw,
 }
 
+impl fmt::Display for CardField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardField::Id => write!(f, "card id"),
+            CardField::Winning => write!(f, "winning numbers"),
+            CardField::Draw => write!(f, "drawn numbers"),
+        }
+    }
+}
+
+/// Errors for a single card line. Spans are byte offsets into the line.
 #[derive(Error, Debug, PartialEq)]
 pub enum CardParseError {
-    #[error(transparent)]
-    ParseError(#[from] std::num::ParseIntError),
+    /// The parse error is part of the message rather than a source, as the
+    /// diagnostic rendering this error already shows it
+    #[error("invalid {field}: {cause}")]
+    ParseError {
+        field: CardField,
+        span: Range<usize>,
+        cause: ParseIntError,
+    },
 
     #[error("Failed to parse card from string: `{0}`")]
     FormatError(String),
+
+    #[error("Number {number} appears more than once in {field}")]
+    DuplicateNumber {
+        number: i32,
+        field: CardField,
+        span: Range<usize>,
+    },
+}
+
+impl CardParseError {
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            CardParseError::ParseError { span, .. } => Some(span.clone()),
+            CardParseError::DuplicateNumber { span, .. } => Some(span.clone()),
+            CardParseError::FormatError(_) => None,
+        }
+    }
+}
+
+/// Whitespace separated tokens of `s` with their byte spans, shifted by
+/// `offset`
+fn spanned_tokens(s: &str, offset: usize) -> Vec<(Range<usize>, &str)> {
+    let mut tokens = vec![];
+    let mut token_start = None;
+    for (i, c) in s.char_indices().chain(std::iter::once((s.len(), ' '))) {
+        match (c.is_whitespace(), token_start) {
+            (true, Some(start)) => {
+                tokens.push((offset + start..offset + i, &s[start..i]));
+                token_start = None;
+            }
+            (false, None) => token_start = Some(i),
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn parse_numbers(
+    s: &str,
+    offset: usize,
+    field: CardField,
+) -> Result<Vec<(Range<usize>, i32)>, CardParseError> {
+    spanned_tokens(s, offset)
+        .into_iter()
+        .map(|(span, token)| match token.parse::<i32>() {
+            Ok(n) => Ok((span, n)),
+            Err(cause) => Err(CardParseError::ParseError { field, span, cause }),
+        })
+        .collect()
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CardTableError {
+    #[error("Card {0} is missing")]
+    Missing(u32),
+
+    #[error("Card {0} appears more than once")]
+    Duplicate(u32),
+
+    #[error("Cards are numbered from 1, found card 0")]
+    Zero,
+
+    #[error("Card {found} comes after card {previous}")]
+    OutOfOrder { previous: u32, found: u32 },
+
+    #[error("Card {card} wins card {won} but the last card is {last}")]
+    PastEnd { card: u32, won: u32, last: u32 },
+}
+
+/// Cards numbered contiguously from 1, in order
+#[derive(Debug)]
+pub struct CardTable {
+    cards: Vec<Card>,
+}
+
+impl CardTable {
+    pub fn new(cards: Vec<Card>) -> Result<Self, CardTableError> {
+        let numbers: HashSet<u32> = cards.iter().map(|c| c.number).collect();
+        let mut seen = HashSet::with_capacity(cards.len());
+        let mut previous = 0;
+        for card in cards.iter() {
+            if card.number == 0 {
+                return Err(CardTableError::Zero);
+            }
+            if card.number < previous {
+                return Err(CardTableError::OutOfOrder {
+                    previous,
+                    found: card.number,
+                });
+            }
+            if !seen.insert(card.number) {
+                return Err(CardTableError::Duplicate(card.number));
+            }
+            // cards skipped here may still come later, out of order
+            if let Some(missing) = (previous + 1..card.number).find(|n| !numbers.contains(n)) {
+                return Err(CardTableError::Missing(missing));
+            }
+            previous = card.number;
+        }
+        Ok(CardTable { cards })
+    }
+
+    pub fn index_of(&self, number: u32) -> Option<usize> {
+        let index = (number as usize).checked_sub(1)?;
+        (index < self.cards.len()).then_some(index)
+    }
 }
 
 impl FromStr for Card {
     type Err = CardParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let split: Vec<_> = s.split(&[':', '|'][..]).collect();
-        if split.len() != 3 {
-            return Err(CardParseError::FormatError(s.to_string()));
+        Card::parse(s, NumberSemantics::Set)
+    }
+}
+
+impl Card {
+    pub fn parse(s: &str, semantics: NumberSemantics) -> Result<Self, CardParseError> {
+        let format_error = || CardParseError::FormatError(s.to_string());
+        let (colon, bar) = match (s.find(':'), s.find('|')) {
+            (Some(colon), Some(bar)) if colon < bar && s.matches(&[':', '|'][..]).count() == 2 => {
+                (colon, bar)
+            }
+            _ => return Err(format_error()),
+        };
+        if !s.starts_with("Card") {
+            return Err(format_error());
+        }
+        let id = match spanned_tokens(&s["Card".len()..colon], "Card".len())[..] {
+            [(ref span, token)] => {
+                token
+                    .parse::<u32>()
+                    .map_err(|cause| CardParseError::ParseError {
+                        field: CardField::Id,
+                        span: span.clone(),
+                        cause,
+                    })?
+            }
+            _ => return Err(format_error()),
+        };
+        let winning_numbers = parse_numbers(&s[colon + 1..bar], colon + 1, CardField::Winning)?;
+        let draw = parse_numbers(&s[bar + 1..], bar + 1, CardField::Draw)?;
+
+        let duplicate = |field: CardField, (span, number): &(Range<usize>, i32)| {
+            CardParseError::DuplicateNumber {
+                number: *number,
+                field,
+                span: span.clone(),
+            }
+        };
+        let mut repeated = 0;
+        let mut winning = HashSet::with_capacity(winning_numbers.len());
+        for n in winning_numbers.iter() {
+            if !winning.insert(n.1) {
+                if semantics == NumberSemantics::Strict {
+                    return Err(duplicate(CardField::Winning, n));
+                }
+                repeated += 1;
+            }
         }
+        let mut drawn = HashSet::with_capacity(draw.len());
+        let mut numbers = Vec::with_capacity(draw.len());
+        for n in draw.iter() {
+            match (drawn.insert(n.1), semantics) {
+                (true, _) => numbers.push(n.1),
+                (false, NumberSemantics::Multiset) => {
+                    numbers.push(n.1);
+                    repeated += 1;
+                }
+                (false, NumberSemantics::Set) => repeated += 1,
+                (false, NumberSemantics::Strict) => return Err(duplicate(CardField::Draw, n)),
+            }
+        }
+
         Ok(Card {
-            number: split[0][4..].trim().parse::<u32>()?,
-            winning: split[1]
-                .split_whitespace()
-                .into_iter()
-                .map(|i| i.parse::<i32>())
-                .collect::<Result<Vec<i32>, std::num::ParseIntError>>()?,
-            draw: split[2]
-                .split_whitespace()
-                .into_iter()
-                .map(|i| i.parse::<i32>())
-                .collect::<Result<Vec<i32>, std::num::ParseIntError>>()?,
+            number: id,
+            winning,
+            draw: numbers,
+            repeated,
         })
     }
-}
 
-impl Card {
-    fn number_matches(&self) -> u32 {
+    pub fn number_matches(&self) -> u32 {
         self.draw
             .iter()
             .filter(|n| self.winning.contains(n))
             .count() as u32
     }
 
-    fn score(&self) -> u32 {
-        let n_wins = self.number_matches();
-        if n_wins == 0 {
-            0
-        } else {
-            2u32.pow((n_wins - 1) as u32)
-        }
+    pub fn score(&self, scoring: &Scoring) -> Result<u128, ScoreError> {
+        scoring.score(self.number_matches())
     }
 }
 
-pub fn run(args: &CommandFourArgs) -> AnyResult<u32> {
-    let file = File::open(args.file.as_str()).expect("Should have been able to read the file");
-    let cards: Vec<Card> = io::BufReader::new(file)
-        .lines()
-        .into_iter()
-        .map(|l| l?.as_str().parse::<Card>().map_err(|e| AnyError::from(e)))
-        .collect::<Result<Vec<Card>, AnyError>>()?;
-
-    if !args.two {
-        let sum = cards.iter().map(|c| c.score()).sum();
-        println!("The sum is: {}", sum);
-        Ok(sum)
-    } else {
-        let mut queue: VecDeque<&Card> = VecDeque::new();
-        for card in cards.iter() {
-            queue.push_back(card);
+/// Counts the scratchcards we end up with. Every card is visited once, adding
+/// its number of copies to each of the cards it wins.
+pub fn count_cards(table: &CardTable, policy: PastEndPolicy) -> AnyResult<u64> {
+    let mut copies = vec![1u64; table.cards.len()];
+    for (i, card) in table.cards.iter().enumerate() {
+        for won in (card.number + 1)..=(card.number + card.number_matches()) {
+            let Some(j) = table.index_of(won) else {
+                match policy {
+                    PastEndPolicy::Clamp => break,
+                    PastEndPolicy::Error => {
+                        return Err(CardTableError::PastEnd {
+                            card: card.number,
+                            won,
+                            last: table.cards.len() as u32,
+                        }
+                        .into())
+                    }
+                }
+            };
+            copies[j] = copies[j]
+                .checked_add(copies[i])
+                .ok_or_else(|| anyhow!("Too many copies of card {}", won))?;
         }
+    }
+    copies
+        .iter()
+        .try_fold(0u64, |acc, &c| acc.checked_add(c))
+        .ok_or_else(|| anyhow!("Too many cards"))
+}
+
+/// Parses every line of `file`, reporting the first bad card with its
+/// position
+pub fn parse(
+    lines: impl Iterator<Item = io::Result<String>>,
+    file: &str,
+    numbers: NumberSemantics,
+) -> AnyResult<Vec<Card>> {
+    lines
+        .enumerate()
+        .map(|(i, l)| {
+            let text = l?;
+            Card::parse(&text, numbers).map_err(|error| {
+                AnyError::from(CardDiagnostic {
+                    file: file.to_string(),
+                    line: i + 1,
+                    text,
+                    error,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Sum of the scores of every card
+pub fn part_one(cards: &[Card], scoring: &Scoring) -> AnyResult<u128> {
+    let mut sum = 0u128;
+    for card in cards.iter() {
+        sum = sum
+            .checked_add(card.score(scoring)?)
+            .ok_or_else(|| anyhow!("Sum of scores does not fit in 128 bits"))?;
+    }
+    Ok(sum)
+}
+
+/// Number of scratchcards once every copy has been won
+pub fn part_two(cards: &[Card], policy: PastEndPolicy) -> AnyResult<u64> {
+    count_cards(&CardTable::new(cards.to_vec())?, policy)
+}
+
+/// Warning about the cards that repeat numbers, which only `strict` rejects
+pub fn repeated_numbers_warning(cards: &[Card]) -> Option<String> {
+    let repeating: Vec<&Card> = cards.iter().filter(|c| c.repeated > 0).collect();
+    if repeating.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "warning: {} repeated numbers on cards {}, see --numbers",
+        repeating.iter().map(|c| c.repeated).sum::<usize>(),
+        repeating.iter().map(|c| c.number).join(", ")
+    ))
+}
+
+pub fn run(args: &CommandFourArgs) -> AnyResult<Answers<u128>> {
+    let file =
+        File::open(&args.file).map_err(|e| anyhow!("Could not read {}: {}", args.file, e))?;
+    let cards = parse(io::BufReader::new(file).lines(), &args.file, args.numbers)?;
+    if let Some(warning) = repeated_numbers_warning(&cards) {
+        eprintln!("{}", warning);
+    }
+    let answers = Answers::solve(
+        args.part,
+        || part_one(&cards, &args.scoring),
+        || part_two(&cards, args.past_end).map(u128::from),
+    )?;
+    println!("{}", answers);
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::VecDeque;
+    use std::time::Instant;
 
+    /// Reference implementation simulating every copy, one at a time
+    fn count_cards_queue(cards: &[Card]) -> u64 {
+        let mut queue: VecDeque<&Card> = cards.iter().collect();
         let mut count = 0;
         while let Some(c) = queue.pop_front() {
             count += 1;
@@ -102,32 +479,188 @@ pub fn run(args: &CommandFourArgs) -> AnyResult<u32> {
                 queue.push_back(&cards[(c.number + i - 1) as usize])
             }
         }
-        println!("The sum is: {}", count);
-        Ok(count)
+        count
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn parse_cards(input: &str) -> Vec<Card> {
+        input.lines().map(|l| l.parse::<Card>().unwrap()).collect()
+    }
+
+    fn parse_table(input: &str) -> CardTable {
+        CardTable::new(parse_cards(input)).unwrap()
+    }
+
+    /// Blocks of ten cards where card `k` of a block has `9 - k` matches, so
+    /// each block ends up with 1023 cards.
+    fn generate_cards(blocks: usize) -> CardTable {
+        let mut input = String::new();
+        for n in 0..blocks * 10 {
+            let matches = 9 - (n % 10);
+            let winning = (1..=10).map(|i| i.to_string()).collect::<Vec<_>>();
+            let draw = (1..=10)
+                .map(|i| if i <= matches { i } else { 50 + i })
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>();
+            input += &format!(
+                "Card {}: {} | {}\n",
+                n + 1,
+                winning.join(" "),
+                draw.join(" ")
+            );
+        }
+        parse_table(&input)
+    }
+
+    #[test]
+    fn test_count_cards_matches_queue() {
+        let table = parse_table(&std::fs::read_to_string("./inputs/four_test.txt").unwrap());
+        assert_eq!(
+            count_cards(&table, PastEndPolicy::Error).unwrap(),
+            count_cards_queue(&table.cards)
+        );
+
+        let table = generate_cards(3);
+        assert_eq!(count_cards(&table, PastEndPolicy::Error).unwrap(), 3 * 1023);
+        assert_eq!(
+            count_cards(&table, PastEndPolicy::Error).unwrap(),
+            count_cards_queue(&table.cards)
+        );
+    }
+
+    #[test]
+    fn test_card_table_errors() {
+        let card = |n: u32| format!("Card {}: 1 2 | 3 4", n);
+        let table = |numbers: &[u32]| {
+            CardTable::new(parse_cards(
+                &numbers
+                    .iter()
+                    .map(|&n| card(n))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ))
+            .map(|_| ())
+        };
+        assert_eq!(table(&[1, 2, 3]), Ok(()));
+        assert_eq!(table(&[2, 3]), Err(CardTableError::Missing(1)));
+        assert_eq!(table(&[0, 1]), Err(CardTableError::Zero));
+        assert_eq!(table(&[1, 2, 4]), Err(CardTableError::Missing(3)));
+        assert_eq!(table(&[1, 2, 2]), Err(CardTableError::Duplicate(2)));
+        assert_eq!(
+            table(&[1, 3, 2]),
+            Err(CardTableError::OutOfOrder {
+                previous: 3,
+                found: 2
+            }),
+        );
+        assert_eq!(table(&[1, 4, 2]), Err(CardTableError::Missing(3)));
+        assert_eq!(
+            table(&[1, 2, 1]),
+            Err(CardTableError::OutOfOrder {
+                previous: 2,
+                found: 1
+            }),
+        );
+    }
+
+    #[test]
+    fn test_count_cards_past_end() {
+        let table = parse_table("Card 1: 1 2 3 | 1 2 3\nCard 2: 1 2 | 1 9");
+        assert_eq!(count_cards(&table, PastEndPolicy::Clamp).unwrap(), 1 + 2);
+        assert_eq!(
+            count_cards(&table, PastEndPolicy::Error)
+                .unwrap_err()
+                .downcast::<CardTableError>()
+                .unwrap(),
+            CardTableError::PastEnd {
+                card: 1,
+                won: 3,
+                last: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_count_cards_overflow() {
+        // every card wins all of the following ones, doubling the total each time
+        let input = (1..=70)
+            .map(|n| {
+                let matches = 70 - n;
+                let numbers = (1..=matches).map(|i| i.to_string()).collect::<Vec<_>>();
+                format!("Card {}: {} | {}", n, numbers.join(" "), numbers.join(" "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(count_cards(&parse_table(&input), PastEndPolicy::Error).is_err());
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_count_cards() {
+        let table = generate_cards(1000);
+
+        let start = Instant::now();
+        let expected = count_cards_queue(&table.cards);
+        println!("queue: {} cards in {:?}", expected, start.elapsed());
+
+        let start = Instant::now();
+        let count = count_cards(&table, PastEndPolicy::Error).unwrap();
+        println!("dp: {} cards in {:?}", count, start.elapsed());
+
+        assert_eq!(count, expected);
+    }
 
     #[test]
     fn test_input() {
         let r = self::run(&CommandFourArgs {
             file: "./inputs/four_test.txt".to_string(),
-            two: false,
+            part: Part::One,
+            past_end: PastEndPolicy::Error,
+            numbers: NumberSemantics::Set,
+            scoring: Scoring::Doubling,
         });
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), 13,);
+        assert_eq!(r.unwrap().one, Some(13));
     }
 
     #[test]
     fn test_input_part_two() {
         let r = self::run(&CommandFourArgs {
             file: "./inputs/four_test.txt".to_string(),
-            two: true,
+            part: Part::Two,
+            past_end: PastEndPolicy::Error,
+            numbers: NumberSemantics::Set,
+            scoring: Scoring::Doubling,
+        });
+        assert_eq!(r.unwrap().two, Some(30));
+    }
+
+    #[test]
+    fn test_both_parts() {
+        let file = "./inputs/four_test.txt";
+        let input = std::fs::read_to_string(file).unwrap();
+        let cards = parse(
+            input.lines().map(|l| Ok(l.to_string())),
+            file,
+            NumberSemantics::Set,
+        )
+        .unwrap();
+        assert_eq!(part_one(&cards, &Scoring::Doubling).unwrap(), 13);
+        assert_eq!(part_two(&cards, PastEndPolicy::Error).unwrap(), 30);
+
+        let r = self::run(&CommandFourArgs {
+            file: file.to_string(),
+            part: Part::Both,
+            past_end: PastEndPolicy::Error,
+            numbers: NumberSemantics::Set,
+            scoring: Scoring::Doubling,
         });
-        assert_eq!(r.unwrap(), 30,);
+        assert_eq!(
+            r.unwrap(),
+            Answers {
+                one: Some(13),
+                two: Some(30)
+            }
+        );
     }
 
     #[test]
@@ -136,8 +669,9 @@ mod tests {
             Card::from_str("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
             Ok(Card {
                 number: 1,
-                winning: vec![41, 48, 83, 86, 17],
+                winning: HashSet::from([41, 48, 83, 86, 17]),
                 draw: vec![83, 86, 6, 31, 17, 9, 48, 53],
+                repeated: 0,
             }),
         );
     }
@@ -146,16 +680,120 @@ mod tests {
     fn test_new_from_line_error() {
         let r = Card::from_str("Card a: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
         assert!(r.is_err());
+        let err = r.err().unwrap();
         assert_eq!(
-            r.err().unwrap().to_string(),
-            "invalid digit found in string"
+            err.to_string(),
+            "invalid card id: invalid digit found in string"
         );
+        assert_eq!(err.span(), Some(5..6));
 
         let r = Card::from_str("Card 1: a 48 83 86 17 | 83 86  6 31 17  9 48 53");
         assert!(r.is_err());
+        let err = r.err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "invalid winning numbers: invalid digit found in string"
+        );
+        assert_eq!(err.span(), Some(8..9));
+
+        let r = Card::from_str("Card 1: 41 | 83 8x6");
+        assert_eq!(r.err().unwrap().span(), Some(16..19));
+
+        for line in [
+            "Card 1 41 | 83",
+            "Card 1: 41 : 83",
+            "Card 1: 41 | 83 | 1",
+            "Cord 1: 1 | 2",
+        ] {
+            assert_eq!(
+                Card::from_str(line),
+                Err(CardParseError::FormatError(line.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_numbers() {
+        let line = "Card 1: 41 48 41 | 41 41 6";
+        let strict = |line: &str| Card::parse(line, NumberSemantics::Strict);
         assert_eq!(
-            r.err().unwrap().to_string(),
-            "invalid digit found in string"
+            strict(line),
+            Err(CardParseError::DuplicateNumber {
+                number: 41,
+                field: CardField::Winning,
+                span: 14..16
+            })
         );
+        assert_eq!(
+            strict("Card 1: 41 48 | 41 6 41"),
+            Err(CardParseError::DuplicateNumber {
+                number: 41,
+                field: CardField::Draw,
+                span: 21..23
+            })
+        );
+
+        let card = Card::from_str(line).unwrap();
+        assert_eq!(card.winning, HashSet::from([41, 48]));
+        assert_eq!(card.draw, vec![41, 6]);
+        assert_eq!(card.number_matches(), 1);
+        assert_eq!(card.repeated, 2);
+
+        let multiset =
+            Card::parse(&line.replace("Card 1", "Card 3"), NumberSemantics::Multiset).unwrap();
+        assert_eq!(multiset.winning, HashSet::from([41, 48]));
+        assert_eq!(multiset.number_matches(), 2);
+        assert_eq!(multiset.repeated, 2);
+
+        // repeats are reported whatever the semantics
+        let plain = Card::from_str("Card 2: 1 2 | 3 4").unwrap();
+        assert_eq!(repeated_numbers_warning(std::slice::from_ref(&plain)), None);
+        assert_eq!(
+            repeated_numbers_warning(&[card, plain, multiset]).unwrap(),
+            "warning: 4 repeated numbers on cards 1, 3, see --numbers"
+        );
+    }
+
+    #[test]
+    fn test_scoring() {
+        let scores = |scoring: Scoring| {
+            (0..=6)
+                .map(|n| scoring.score(n).unwrap())
+                .collect::<Vec<u128>>()
+        };
+        assert_eq!(scores(Scoring::Doubling), vec![0, 1, 2, 4, 8, 16, 32]);
+        assert_eq!(scores(Scoring::Linear), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(scores(Scoring::Fibonacci), vec![0, 1, 1, 2, 3, 5, 8]);
+        assert_eq!(
+            scores("table:1,3,9,27,81,243".parse::<Scoring>().unwrap()),
+            vec![0, 1, 3, 9, 27, 81, 243]
+        );
+
+        assert_eq!(Scoring::Doubling.score(128), Ok(1 << 127));
+        assert_eq!(Scoring::Doubling.score(129), Err(ScoreError::Overflow(129)));
+        assert!(Scoring::Fibonacci.score(186).is_ok());
+        assert_eq!(
+            Scoring::Fibonacci.score(187),
+            Err(ScoreError::Overflow(187))
+        );
+        assert_eq!(
+            Scoring::Table(vec![1]).score(2),
+            Err(ScoreError::MissingTableEntry(2))
+        );
+
+        assert!("table:1,x".parse::<Scoring>().is_err());
+        assert!("quadratic".parse::<Scoring>().is_err());
+    }
+
+    #[test]
+    fn test_input_scoring() {
+        let r = self::run(&CommandFourArgs {
+            file: "./inputs/four_test.txt".to_string(),
+            part: Part::One,
+            past_end: PastEndPolicy::Error,
+            numbers: NumberSemantics::Set,
+            scoring: Scoring::Linear,
+        });
+        assert_eq!(r.unwrap().one, Some(4 + 2 + 2 + 1));
     }
 }
@@ -0,0 +1,105 @@
+use std::{error::Error, fmt};
+
+use super::CardParseError;
+
+/// A card parse error together with where it happened, rendered like a
+/// compiler diagnostic with a caret under the offending token.
+#[derive(Debug, PartialEq)]
+pub struct CardDiagnostic {
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+    pub error: CardParseError,
+}
+
+impl fmt::Display for CardDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.error.span().unwrap_or(0..self.text.len());
+        // spans are in bytes, the caret is placed in characters
+        let column = self.text[..span.start].chars().count();
+        let width = self.text[span].chars().count().max(1);
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        writeln!(f, "error: {}", self.error)?;
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter,
+            self.file,
+            self.line,
+            column + 1
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.text)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(column),
+            "^".repeat(width)
+        )
+    }
+}
+
+// no source: the rendering already includes the card error and its cause
+impl Error for CardDiagnostic {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Card, NumberSemantics};
+    use super::*;
+
+    fn diagnose(line: usize, text: &str) -> String {
+        CardDiagnostic {
+            file: "four.txt".to_string(),
+            line,
+            text: text.to_string(),
+            error: Card::parse(text, NumberSemantics::Set).unwrap_err(),
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn no_repeated_causes() {
+        let error = anyhow::Error::from(CardDiagnostic {
+            file: "four.txt".to_string(),
+            line: 3,
+            text: "Card 3: 41 4x8 | 83 86".to_string(),
+            error: Card::parse("Card 3: 41 4x8 | 83 86", NumberSemantics::Set).unwrap_err(),
+        });
+        assert_eq!(error.chain().count(), 1);
+        assert_eq!(
+            format!("{:#}", error),
+            diagnose(3, "Card 3: 41 4x8 | 83 86")
+        );
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            diagnose(3, "Card 3: 41 4x8 | 83 86"),
+            "error: invalid winning numbers: invalid digit found in string
+ --> four.txt:3:12
+  |
+3 | Card 3: 41 4x8 | 83 86
+  |            ^^^"
+        );
+        assert_eq!(
+            diagnose(12, "Card 12: 1 | 5 é 5"),
+            "error: invalid drawn numbers: invalid digit found in string
+  --> four.txt:12:16
+   |
+12 | Card 12: 1 | 5 é 5
+   |                ^"
+        );
+        assert_eq!(
+            diagnose(1, "Card 1 1 2"),
+            "error: Failed to parse card from string: `Card 1 1 2`
+ --> four.txt:1:1
+  |
+1 | Card 1 1 2
+  | ^^^^^^^^^^"
+        );
+    }
+}
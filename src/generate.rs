@@ -0,0 +1,357 @@
+use anyhow::Result as AnyResult;
+
+use std::fs;
+
+use clap::Args;
+use itertools::Itertools;
+
+use crate::day::Day;
+use crate::seven::rules::CARDS;
+
+#[derive(Args, Debug)]
+pub struct CommandGenerateArgs {
+    pub day: Day,
+
+    /// How big an input to make: lines for day one, games for two, rows and
+    /// columns for three, cards for four, seed ranges for five, races for
+    /// six and hands for seven
+    #[clap(long, short = 'n', default_value_t = 100)]
+    pub size: usize,
+
+    /// The same seed always gives the same input
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// File to write the input to, instead of printing it
+    #[clap(long, short)]
+    pub output: Option<String>,
+}
+
+/// Small deterministic random number generator (SplitMix64), so that inputs
+/// only depend on their seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// A number in `low..=high`
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    /// True with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= p
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    /// `count` distinct numbers from `low..=high`, in random order
+    pub fn distinct(&mut self, count: usize, low: u64, high: u64) -> Vec<u64> {
+        let mut pool: Vec<u64> = (low..=high).collect();
+        for i in 0..count {
+            let j = i + self.below((pool.len() - i) as u64) as usize;
+            pool.swap(i, j);
+        }
+        pool.truncate(count);
+        pool
+    }
+}
+
+const DIGITS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Calibration lines mixing letters, digits and spelled out digits. Every
+/// line has at least one digit.
+fn one(size: usize, rng: &mut Rng) -> String {
+    let letters: Vec<char> = ('a'..='z').collect();
+    (0..size)
+        .map(|_| {
+            let mut line = String::new();
+            let digit = rng.between(1, 9).to_string();
+            let at = rng.between(0, 8);
+            for i in 0..rng.between(1, 8) {
+                if i == at {
+                    line += &digit;
+                }
+                match rng.below(4) {
+                    0 => line += &rng.between(1, 9).to_string(),
+                    1 => line += *rng.choose(&DIGITS),
+                    _ => (0..rng.between(1, 5)).for_each(|_| line.push(*rng.choose(&letters))),
+                }
+            }
+            if !line.contains(|c: char| c.is_ascii_digit()) {
+                line += &digit;
+            }
+            line
+        })
+        .join("\n")
+}
+
+fn two(size: usize, rng: &mut Rng) -> String {
+    (1..=size)
+        .map(|id| {
+            let draws = (0..rng.between(1, 6))
+                .map(|_| {
+                    let count = rng.between(1, 3) as usize;
+                    rng.distinct(count, 0, 2)
+                        .iter()
+                        .map(|&c| {
+                            let color = ["red", "green", "blue"][c as usize];
+                            format!("{} {}", rng.between(1, 20), color)
+                        })
+                        .join(", ")
+                })
+                .join("; ");
+            format!("Game {}: {}", id, draws)
+        })
+        .join("\n")
+}
+
+/// A square engine schematic of numbers and symbols
+fn three(size: usize, rng: &mut Rng) -> String {
+    let symbols: Vec<char> = "*#+$/@=%-&".chars().collect();
+    (0..size)
+        .map(|_| {
+            let mut row = String::new();
+            while row.len() < size {
+                let left = size - row.len();
+                if rng.chance(0.1) {
+                    let number = rng.between(1, 999).to_string();
+                    if number.len() <= left {
+                        row += &number;
+                        // keep numbers apart
+                        if row.len() < size {
+                            row.push('.');
+                        }
+                        continue;
+                    }
+                }
+                if rng.chance(0.06) {
+                    row.push(*rng.choose(&symbols));
+                } else {
+                    row.push('.');
+                }
+            }
+            row
+        })
+        .join("\n")
+}
+
+/// Scratchcards with ten winning numbers and twenty five drawn ones. Cards
+/// come in blocks of ten whose wins stay within the block, so the number of
+/// copies won stays small whatever the size.
+fn four(size: usize, rng: &mut Rng) -> String {
+    let numbers = |values: &[u64]| values.iter().map(|n| format!("{:>2}", n)).join(" ");
+    (0..size)
+        .map(|i| {
+            let room = (9 - i % 10).min(size - 1 - i) as u64;
+            let matches = match rng.below(3) {
+                0 => 0,
+                _ => rng.between(0, room.min(4)),
+            } as usize;
+            let pool = rng.distinct(10 + 25 - matches, 1, 99);
+            let winning = &pool[..10];
+            let mut draw: Vec<u64> = pool[10..].to_vec();
+            draw.extend(&winning[..matches]);
+            for j in (1..draw.len()).rev() {
+                draw.swap(j, rng.below(j as u64 + 1) as usize);
+            }
+            format!(
+                "Card {:>3}: {} | {}",
+                i + 1,
+                numbers(winning),
+                numbers(&draw)
+            )
+        })
+        .join("\n")
+}
+
+/// An almanac with `size` seed ranges and the usual seven maps
+fn five(size: usize, rng: &mut Rng) -> String {
+    let span: u64 = 1 << 32;
+    let seeds = (0..size)
+        .flat_map(|_| [rng.below(span), rng.between(1, span / 10_000)])
+        .join(" ");
+    let names = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let maps = names
+        .iter()
+        .tuple_windows()
+        .map(|(from, to)| {
+            // source ranges that do not overlap, with gaps between some
+            let count = rng.between(10, 40);
+            let mut start = rng.below(span / count);
+            let mappings = (0..count)
+                .map(|_| {
+                    let len = rng.between(1, span / count);
+                    let mapping = format!("{} {} {}", rng.below(span), start, len);
+                    start += len;
+                    if rng.chance(0.3) {
+                        start += rng.below(span / count / 4);
+                    }
+                    mapping
+                })
+                .join("\n");
+            format!("{}-to-{} map:\n{}", from, to, mappings)
+        })
+        .join("\n\n");
+    format!("seeds: {}\n\n{}", seeds, maps)
+}
+
+/// Races that can always be won
+fn six(size: usize, rng: &mut Rng) -> String {
+    let races: Vec<(u64, u64)> = (0..size)
+        .map(|_| {
+            let time = rng.between(7, 100);
+            // holding the button for half the race goes the furthest
+            let best = (time / 2) * (time - time / 2);
+            (time, rng.below(best))
+        })
+        .collect();
+    let row = |values: Vec<u64>| values.iter().map(|v| format!("{:>5}", v)).join("");
+    format!(
+        "Time:    {}\nDistance:{}",
+        row(races.iter().map(|r| r.0).collect()),
+        row(races.iter().map(|r| r.1).collect())
+    )
+}
+
+fn seven(size: usize, rng: &mut Rng) -> String {
+    let cards: Vec<char> = CARDS.chars().collect();
+    (0..size)
+        .map(|_| {
+            let hand: String = (0..5).map(|_| *rng.choose(&cards)).collect();
+            format!("{} {}", hand, rng.between(1, 1000))
+        })
+        .join("\n")
+}
+
+/// A random input for `day`, without a trailing newline
+pub fn generate(day: Day, size: usize, seed: u64) -> String {
+    let rng = &mut Rng::new(seed);
+    match day {
+        Day::One => one(size, rng),
+        Day::Two => two(size, rng),
+        Day::Three => three(size, rng),
+        Day::Four => four(size, rng),
+        Day::Five => five(size, rng),
+        Day::Six => six(size, rng),
+        Day::Seven => seven(size, rng),
+    }
+}
+
+pub fn run(args: &CommandGenerateArgs) -> AnyResult<()> {
+    let input = generate(args.day, args.size, args.seed);
+    match &args.output {
+        Some(file) => fs::write(file, input + "\n")?,
+        None => println!("{}", input),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::ValueEnum;
+
+    use crate::{five, four, one, seven, three, two};
+
+    fn lines(input: &str) -> impl Iterator<Item = String> + '_ {
+        input.lines().map(String::from)
+    }
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in Day::value_variants() {
+            assert_eq!(generate(*day, 20, 7), generate(*day, 20, 7));
+            assert_ne!(generate(*day, 20, 7), generate(*day, 20, 8));
+        }
+    }
+
+    #[test]
+    fn inputs_solve() {
+        for seed in 0..20 {
+            let input = generate(Day::One, 50, seed);
+            let calibration: Vec<String> = lines(&input).collect();
+            assert!(calibration
+                .iter()
+                .all(|l| one::parse_line(l.clone(), false).is_ok()));
+            assert_eq!(calibration.len(), 50);
+
+            let games = two::parse(lines(&generate(Day::Two, 50, seed))).unwrap();
+            assert_eq!(games.len(), 50);
+            assert!(two::part_one(&games, &two::DEFAULT_BAG) > 0);
+
+            let input = generate(Day::Three, 30, seed);
+            assert!(input.lines().all(|l| l.len() == 30));
+            let (numbers, symbols) = three::parse_tokens(lines(&input)).unwrap();
+            assert!(numbers.iter().all(|n| n.value > 0 && n.value < 1000));
+            three::part_two(&numbers, &symbols).unwrap();
+
+            let input = generate(Day::Four, 95, seed);
+            let cards = four::parse(lines(&input).map(Ok), "", four::NumberSemantics::Set).unwrap();
+            four::part_one(&cards, &four::Scoring::Doubling).unwrap();
+            four::part_two(&cards, four::PastEndPolicy::Error).unwrap();
+
+            let almanac = five::Almanac::from_lines(lines(&generate(Day::Five, 5, seed))).unwrap();
+            assert_eq!(almanac.seeds.len(), 10);
+            assert_eq!(almanac.collections.len(), 7);
+            let lowest = five::part_two(&almanac).unwrap();
+            assert!(almanac
+                .seeds
+                .iter()
+                .step_by(2)
+                .all(|&seed| almanac.location(seed) >= lowest));
+            five::part_one(&almanac).unwrap();
+
+            let bids = seven::parse(lines(&generate(Day::Seven, 50, seed))).unwrap();
+            seven::part_two(&bids).unwrap();
+        }
+    }
+
+    #[test]
+    fn races_can_be_won() {
+        let input = generate(Day::Six, 10, 3);
+        let rows: Vec<Vec<u64>> = input
+            .lines()
+            .map(|l| {
+                l.split_whitespace()
+                    .skip(1)
+                    .map(|n| n.parse().unwrap())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows[0].len(), 10);
+        for (time, distance) in rows[0].iter().zip(rows[1].iter()) {
+            assert!((0..*time).any(|hold| hold * (time - hold) > *distance));
+        }
+    }
+}
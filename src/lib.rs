@@ -0,0 +1,31 @@
+//! Advent of Code 2023 solutions.
+//!
+//! Each day module exposes a parser producing the day's input model, a
+//! `part_one` and a `part_two` function solving on that model, and the `run`
+//! entry point used by the `aoc` command line tool.
+//!
+//! ```
+//! let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
+//! let games = aoc::two::parse(input.lines().map(String::from)).unwrap();
+//! assert_eq!(aoc::two::part_two(&games).unwrap(), 4 * 2 * 6);
+//! ```
+
+pub mod all;
+pub mod check;
+pub mod client;
+pub mod day;
+pub mod executor;
+pub mod fetch;
+pub mod five;
+pub mod four;
+pub mod generate;
+pub mod one;
+pub mod parallel;
+pub mod part;
+pub mod sample;
+pub mod seven;
+pub mod submit;
+pub mod three;
+pub mod two;
+
+pub use day::Day;
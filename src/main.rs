@@ -1,17 +1,21 @@
-pub mod seven;
-pub mod five;
-pub mod four;
-pub mod one;
-pub mod three;
-pub mod two;
+use anyhow::Result as AnyResult;
 
-use seven::CommandSevenArgs;
-use five::CommandFiveArgs;
-use four::CommandFourArgs;
-use one::CommandOneArgs;
-use three::CommandThreeArgs;
-use two::CommandTwoArgs;
+use std::process::ExitCode;
 
+use aoc::all::CommandAllArgs;
+use aoc::check::CommandCheckArgs;
+use aoc::fetch::CommandFetchArgs;
+use aoc::five::CommandFiveArgs;
+use aoc::four::CommandFourArgs;
+use aoc::generate::CommandGenerateArgs;
+use aoc::one::CommandOneArgs;
+use aoc::sample::CommandSampleArgs;
+use aoc::seven::CommandSevenArgs;
+use aoc::submit::CommandSubmitArgs;
+use aoc::three::CommandThreeArgs;
+use aoc::two::CommandTwoArgs;
+
+use aoc::{all, check, fetch, five, four, generate, one, sample, seven, submit, three, two};
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -29,28 +33,71 @@ enum Commands {
     Four(CommandFourArgs),
     Five(CommandFiveArgs),
     Seven(CommandSevenArgs),
+    /// Solve every day in parallel
+    All(CommandAllArgs),
+    /// Print a random input for a day
+    Generate(CommandGenerateArgs),
+    /// Compare solvers with slow reference ones on random inputs
+    Check(CommandCheckArgs),
+    /// Download the input of a day, unless it is already saved
+    Fetch(CommandFetchArgs),
+    /// Submit the answer to a part of a day
+    Submit(CommandSubmitArgs),
+    /// Save the examples of a saved puzzle page and print a test for them to
+    /// paste into the day's tests
+    Sample(CommandSampleArgs),
 }
 
-fn main() {
-    let args = Cli::parse();
-    match &args.command {
+fn run(command: &Commands) -> AnyResult<()> {
+    match command {
         Commands::One(cmd_args) => {
-            let _ = one::run(cmd_args);
+            one::run(cmd_args)?;
         }
         Commands::Two(cmd_args) => {
-            let _ = two::run(cmd_args);
+            two::run(cmd_args)?;
         }
         Commands::Three(cmd_args) => {
-            let _ = three::run(cmd_args);
+            three::run(cmd_args)?;
         }
         Commands::Four(cmd_args) => {
-            let _ = four::run(&cmd_args).unwrap();
+            four::run(cmd_args)?;
         }
         Commands::Five(cmd_args) => {
-            let _ = five::run(&cmd_args).unwrap();
+            five::run(cmd_args)?;
         }
         Commands::Seven(cmd_args) => {
-            let _ = seven::run(&cmd_args).unwrap();
+            seven::run(cmd_args)?;
+        }
+        Commands::All(cmd_args) => {
+            all::run(cmd_args)?;
+        }
+        Commands::Generate(cmd_args) => {
+            generate::run(cmd_args)?;
+        }
+        Commands::Check(cmd_args) => {
+            check::run(cmd_args)?;
+        }
+        Commands::Fetch(cmd_args) => {
+            fetch::run(cmd_args)?;
+        }
+        Commands::Submit(cmd_args) => {
+            submit::run(cmd_args)?;
+        }
+        Commands::Sample(cmd_args) => {
+            sample::run(cmd_args)?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = Cli::parse();
+    match run(&args.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // the whole chain on one line, without a panic around it
+            eprintln!("{:#}", e);
+            ExitCode::FAILURE
         }
     }
 }
@@ -1,3 +1,6 @@
+use anyhow::anyhow;
+use anyhow::Result as AnyResult;
+
 use std::{
     error::Error,
     fs::File,
@@ -6,12 +9,15 @@ use std::{
 
 use clap::Args;
 
+use crate::part::{Answers, Part};
+
 #[derive(Args, Debug)]
 pub struct CommandOneArgs {
-    file: String,
+    pub file: String,
 
-    #[clap(long, short = '2', action)]
-    two: bool,
+    /// Parts to solve
+    #[clap(long, value_enum, default_value_t = Part::Both)]
+    pub part: Part,
 }
 
 const RADIX: u32 = 10;
@@ -19,63 +25,95 @@ const DIGITS: [&str; 9] = [
     "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
 
-fn parse_line(line: String, part_two: bool) -> Result<u32, Box<dyn Error>> {
-    let mut i = 0;
+pub fn parse_line(line: String, part_two: bool) -> Result<u32, Box<dyn Error>> {
     let mut line_numbers: Vec<u32> = vec![];
-    while i < line.len() {
+    for (i, c) in line.char_indices() {
         if part_two {
-            for (j, &digit) in DIGITS.iter().enumerate() {
-                let len = digit.len();
-                if len <= (line.len() - i) && line[i..i + len] == *digit {
-                    line_numbers.push(j as u32 + 1);
-                    break;
-                }
+            if let Some(j) = DIGITS.iter().position(|digit| line[i..].starts_with(digit)) {
+                line_numbers.push(j as u32 + 1);
             }
         }
-        let c = line.chars().nth(i).unwrap();
-        match c.to_digit(RADIX) {
-            Some(d) => line_numbers.push(d),
-            None => {}
+        if let Some(d) = c.to_digit(RADIX) {
+            line_numbers.push(d);
         }
-        i += 1
     }
-    if line_numbers.len() == 0 {
+    if line_numbers.is_empty() {
         return Err(format!("Could not parse line: {}", line).into());
     }
     Ok(line_numbers.first().unwrap() * RADIX + line_numbers.last().unwrap())
 }
 
-pub fn run(args: &CommandOneArgs) -> u32 {
-    let file = File::open(args.file.as_str()).expect("Should have been able to read the file");
-    let lines = io::BufReader::new(file).lines();
-    let sum: u32 = lines
-        .into_iter()
-        .filter_map(|line| parse_line(line.unwrap(), args.two).ok())
-        .sum();
-    println!("The sum is: {}", sum);
-    sum
+/// Sum of the calibration values of every line that has one
+fn calibration_sum(lines: &[String], part_two: bool) -> u32 {
+    lines
+        .iter()
+        .filter_map(|line| parse_line(line.clone(), part_two).ok())
+        .sum()
+}
+
+pub fn part_one(lines: &[String]) -> u32 {
+    calibration_sum(lines, false)
+}
+
+pub fn part_two(lines: &[String]) -> u32 {
+    calibration_sum(lines, true)
+}
+
+pub fn run(args: &CommandOneArgs) -> AnyResult<Answers<u32>> {
+    let file =
+        File::open(&args.file).map_err(|e| anyhow!("Could not read {}: {}", args.file, e))?;
+    let lines = io::BufReader::new(file)
+        .lines()
+        .collect::<io::Result<Vec<String>>>()?;
+    let answers = Answers::of(args.part, || part_one(&lines), || part_two(&lines));
+    println!("{}", answers);
+    Ok(answers)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("éight2three".to_string(), true).unwrap(), 23);
+        assert_eq!(parse_line("ab1ç".to_string(), false).unwrap(), 11);
+        assert!(parse_line("øne".to_string(), true).is_err());
+    }
+
     #[test]
     fn test_sample() {
         assert_eq!(
             run(&CommandOneArgs {
                 file: String::from("./inputs/one_test.txt"),
-                two: false
-            }),
-            209
+                part: Part::Both
+            })
+            .unwrap(),
+            Answers {
+                one: Some(209),
+                two: Some(281)
+            }
         );
 
         assert_eq!(
             run(&CommandOneArgs {
                 file: String::from("./inputs/one_test.txt"),
-                two: true
-            }),
-            281
+                part: Part::Two
+            })
+            .unwrap(),
+            Answers {
+                one: None,
+                two: Some(281)
+            }
         );
+
+        let missing = run(&CommandOneArgs {
+            file: String::from("./inputs/missing.txt"),
+            part: Part::Both,
+        });
+        assert!(missing
+            .unwrap_err()
+            .to_string()
+            .starts_with("Could not read ./inputs/missing.txt"));
     }
 }
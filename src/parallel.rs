@@ -0,0 +1,58 @@
+#[cfg(not(feature = "parallel"))]
+use anyhow::bail;
+use anyhow::Result as AnyResult;
+
+/// Runs `f` on a pool of `threads` worker threads, or on rayon's global pool
+/// when `threads` is `None`. The data-parallel loops inside `f` use that
+/// pool. Without the `parallel` feature, `f` simply runs on this thread, and
+/// asking for a number of threads is an error.
+#[cfg(feature = "parallel")]
+pub fn install<R: Send>(threads: Option<usize>, f: impl FnOnce() -> R + Send) -> AnyResult<R> {
+    match threads {
+        Some(threads) => Ok(rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?
+            .install(f)),
+        None => Ok(f()),
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn install<R: Send>(threads: Option<usize>, f: impl FnOnce() -> R + Send) -> AnyResult<R> {
+    if threads.is_some() {
+        bail!("--threads needs the parallel feature");
+    }
+    Ok(f())
+}
+
+/// Thread counts for benchmarks to compare: a single thread and every CPU,
+/// or only this thread without the `parallel` feature
+#[cfg(test)]
+pub fn bench_threads() -> Vec<Option<usize>> {
+    match cfg!(feature = "parallel") {
+        true => vec![Some(1), None],
+        false => vec![None],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "parallel"))]
+    fn threads_need_feature() {
+        assert_eq!(install(None, || 1).unwrap(), 1);
+        assert!(install(Some(2), || 1).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn thread_count() {
+        assert_eq!(install(Some(3), rayon::current_num_threads).unwrap(), 3);
+        assert_eq!(
+            install(None, rayon::current_num_threads).unwrap(),
+            rayon::current_num_threads()
+        );
+    }
+}
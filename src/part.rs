@@ -0,0 +1,87 @@
+use std::convert::Infallible;
+use std::fmt;
+
+use clap::ValueEnum;
+
+/// Which parts of a puzzle to solve
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Part {
+    #[value(name = "1")]
+    One,
+    #[value(name = "2")]
+    Two,
+    Both,
+}
+
+/// Answers to the parts that were solved
+#[derive(Debug, PartialEq)]
+pub struct Answers<T> {
+    pub one: Option<T>,
+    pub two: Option<T>,
+}
+
+impl<T> Answers<T> {
+    /// Runs `one` and `two` as selected by `part`, part one first
+    pub fn solve<E>(
+        part: Part,
+        one: impl FnOnce() -> Result<T, E>,
+        two: impl FnOnce() -> Result<T, E>,
+    ) -> Result<Self, E> {
+        let one = match part {
+            Part::One | Part::Both => Some(one()?),
+            Part::Two => None,
+        };
+        let two = match part {
+            Part::Two | Part::Both => Some(two()?),
+            Part::One => None,
+        };
+        Ok(Answers { one, two })
+    }
+
+    /// Like `solve`, for parts that cannot fail
+    pub fn of(part: Part, one: impl FnOnce() -> T, two: impl FnOnce() -> T) -> Self {
+        match Answers::solve::<Infallible>(part, || Ok(one()), || Ok(two())) {
+            Ok(answers) => answers,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Answers<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = [("Part 1", &self.one), ("Part 2", &self.two)];
+        let mut first = true;
+        for (label, answer) in lines {
+            if let Some(answer) = answer {
+                if !first {
+                    writeln!(f)?;
+                }
+                write!(f, "{}: {}", label, answer)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_selected_parts() {
+        let solve = |part| Answers::solve::<()>(part, || Ok(1), || Ok(2)).unwrap();
+        assert_eq!(
+            solve(Part::One),
+            Answers {
+                one: Some(1),
+                two: None
+            }
+        );
+        assert_eq!(solve(Part::Two).to_string(), "Part 2: 2");
+        assert_eq!(solve(Part::Both).to_string(), "Part 1: 1\nPart 2: 2");
+
+        let failed = Answers::solve(Part::Both, || Err("bad input"), || Ok(2));
+        assert_eq!(failed, Err("bad input"));
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Result as AnyResult;
+
+use std::fs;
+use std::path::Path;
+
+use clap::Args;
+
+use crate::day::Day;
+
+#[derive(Args, Debug)]
+pub struct CommandSampleArgs {
+    pub day: Day,
+
+    /// Puzzle description saved from the browser
+    pub page: String,
+
+    /// Directory to write the sample inputs to, e.g. `one_test.txt`
+    #[clap(long, default_value = "inputs")]
+    pub inputs: String,
+
+    /// Replace sample inputs that already exist
+    #[clap(long, action)]
+    pub force: bool,
+}
+
+/// The example of one part of a puzzle, and its answer
+#[derive(Debug, PartialEq)]
+pub struct Example {
+    pub input: String,
+    pub answer: String,
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(&text)
+}
+
+/// Contents of every `<open>...</close>` in `html`, in order
+fn elements<'a>(html: &'a str, open: &str, close: &str) -> Vec<&'a str> {
+    let mut found = vec![];
+    let mut rest = html;
+    while let Some(start) = rest.find(open) {
+        let inner = &rest[start + open.len()..];
+        let Some(end) = inner.find(close) else {
+            break;
+        };
+        found.push(&inner[..end]);
+        rest = &inner[end + close.len()..];
+    }
+    found
+}
+
+/// The examples of the parts of a puzzle page, part one first. A part
+/// without an example block of its own reuses the one before it. Its answer
+/// is the last emphasized code of its description.
+pub fn extract(html: &str) -> AnyResult<Vec<Example>> {
+    let mut examples: Vec<Example> = vec![];
+    for article in elements(html, "<article", "</article>") {
+        let input = elements(article, "<pre><code>", "</code></pre>")
+            .first()
+            .map(|block| strip_tags(block))
+            .or_else(|| examples.last().map(|e| e.input.clone()))
+            .ok_or_else(|| anyhow!("Part {} has no example", examples.len() + 1))?;
+        let answer = elements(article, "<code><em>", "</em></code>")
+            .last()
+            .map(|answer| strip_tags(answer).trim().to_string())
+            .ok_or_else(|| anyhow!("Part {} has no answer", examples.len() + 1))?;
+        examples.push(Example { input, answer });
+    }
+    if examples.is_empty() {
+        bail!("No puzzle description found");
+    }
+    Ok(examples)
+}
+
+/// Name of the sample input of a part, relative to the inputs directory.
+/// Part two only gets its own file when its example differs.
+pub fn sample_file(day: Day, examples: &[Example], part: usize) -> String {
+    match part > 0 && examples[part].input != examples[0].input {
+        true => format!("{}_test_two.txt", day.name()),
+        false => format!("{}_test.txt", day.name()),
+    }
+}
+
+/// Name of the test for the samples of `page`, unique among the tests of a
+/// day as long as the pages are, e.g. `test_sample_seven_day7`
+pub fn test_name(day: Day, page: &str) -> String {
+    let stem = Path::new(page)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let stem: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("test_sample_{}_{}", day.name(), stem.trim_matches('_'))
+}
+
+/// A test named `name` for the module of `day` solving its samples
+pub fn sample_test(day: Day, name: &str, examples: &[Example]) -> AnyResult<String> {
+    if day == Day::Six {
+        bail!("Day six is not solved yet");
+    }
+    let checks: String = examples
+        .iter()
+        .enumerate()
+        .map(|(i, example)| {
+            format!(
+                "        assert_eq!(answer({:?}, {}), {:?});\n",
+                format!("./inputs/{}", sample_file(day, examples, i)),
+                i + 1,
+                example.answer
+            )
+        })
+        .collect();
+    Ok(format!(
+        "    #[test]
+    fn {}() {{
+        let answer = |file, part| crate::all::answer_file(file, {:?}, part).unwrap();
+{}    }}
+",
+        name,
+        day.name(),
+        checks
+    ))
+}
+
+/// Saves the sample inputs of a puzzle page and returns the test checking
+/// their answers. The test is only printed, to be pasted into the tests of
+/// `src/<day>.rs` by hand. Nothing is written unless the test can be made
+/// and every sample file can be saved.
+pub fn run(args: &CommandSampleArgs) -> AnyResult<String> {
+    let html = fs::read_to_string(&args.page)
+        .map_err(|e| anyhow!("Could not read {}: {}", args.page, e))?;
+    let examples = extract(&html)?;
+    let test = sample_test(args.day, &test_name(args.day, &args.page), &examples)?;
+    let samples: Vec<_> = examples
+        .iter()
+        .enumerate()
+        .filter(|(i, example)| *i == 0 || example.input != examples[0].input)
+        .map(|(i, example)| {
+            let path = Path::new(&args.inputs).join(sample_file(args.day, &examples, i));
+            (path, example)
+        })
+        .collect();
+    if let Some((path, _)) = samples.iter().find(|(path, _)| path.exists()) {
+        if !args.force {
+            bail!(
+                "{} exists already, use --force to replace it",
+                path.display()
+            );
+        }
+    }
+    for (path, example) in samples {
+        fs::write(&path, &example.input)
+            .map_err(|e| anyhow!("Could not write {}: {}", path.display(), e))?;
+        eprintln!("Saved {}", path.display());
+    }
+    println!("{}", test);
+    Ok(test)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body><main>
+<article class="day-desc"><h2>--- Day 7: Camel Cards ---</h2>
+<p>For example:</p>
+<pre><code>32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+</code></pre>
+<p>Here, <code>KK677</code> wins. So the total winnings are <code><em>6440</em></code>.</p>
+</article>
+<p>Your puzzle answer was <code>252656917</code>.</p>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>Using the same example, the total is now <code><em>5905</em></code>.</p>
+</article>
+</main></body></html>"#;
+
+    #[test]
+    fn extract_examples() {
+        let examples = extract(PAGE).unwrap();
+        assert_eq!(examples.len(), 2);
+        assert_eq!(
+            examples[0].input.trim_end(),
+            std::fs::read_to_string("./inputs/seven_test.txt").unwrap()
+        );
+        assert_eq!(examples[0].answer, "6440");
+        assert_eq!(examples[1].input, examples[0].input);
+        assert_eq!(examples[1].answer, "5905");
+        assert!(extract("<html>Log in</html>").is_err());
+    }
+
+    #[test]
+    fn separate_examples() {
+        let page = "<article><pre><code>1abc2\n</code></pre><code><em>12</em></code></article>\
+            <article><pre><code><em>two</em>1nine &amp;\n</code></pre>\
+            <code><em>29</em></code></article>";
+        let examples = extract(page).unwrap();
+        assert_eq!(examples[1].input, "two1nine &\n");
+        assert_eq!(sample_file(Day::One, &examples, 0), "one_test.txt");
+        assert_eq!(sample_file(Day::One, &examples, 1), "one_test_two.txt");
+    }
+
+    #[test]
+    fn generated_test() {
+        let examples = extract(PAGE).unwrap();
+        assert_eq!(
+            sample_test(Day::Seven, "test_sample_seven_day7", &examples).unwrap(),
+            r#"    #[test]
+    fn test_sample_seven_day7() {
+        let answer = |file, part| crate::all::answer_file(file, "seven", part).unwrap();
+        assert_eq!(answer("./inputs/seven_test.txt", 1), "6440");
+        assert_eq!(answer("./inputs/seven_test.txt", 2), "5905");
+    }
+"#
+        );
+        assert!(sample_test(Day::Six, "test_sample_six", &examples).is_err());
+
+        assert_eq!(
+            test_name(Day::Seven, "pages/day7.html"),
+            "test_sample_seven_day7"
+        );
+        assert_eq!(
+            test_name(Day::One, "Day 1 (part 2).html"),
+            "test_sample_one_day_1__part_2"
+        );
+    }
+
+    #[test]
+    fn saves_samples() {
+        let inputs = std::env::temp_dir().join(format!("aoc-sample-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&inputs);
+        fs::create_dir_all(&inputs).unwrap();
+        let page = inputs.join("day7.html");
+        fs::write(&page, PAGE).unwrap();
+        let args = |day, force| CommandSampleArgs {
+            day,
+            page: page.to_string_lossy().to_string(),
+            inputs: inputs.to_string_lossy().to_string(),
+            force,
+        };
+
+        // without a test for the day, no sample is saved either
+        assert!(run(&args(Day::Six, false)).is_err());
+        assert!(!inputs.join("six_test.txt").exists());
+
+        let test = run(&args(Day::Seven, false)).unwrap();
+        let examples = extract(PAGE).unwrap();
+        let expected = sample_test(Day::Seven, "test_sample_seven_day7", &examples).unwrap();
+        assert_eq!(test, expected);
+        let saved = fs::read_to_string(inputs.join("seven_test.txt")).unwrap();
+        assert!(saved.starts_with("32T3K 765\n"));
+        assert!(run(&args(Day::Seven, false)).is_err());
+        assert!(run(&args(Day::Seven, true)).is_ok());
+        fs::remove_dir_all(&inputs).unwrap();
+    }
+}
@@ -1,116 +1,287 @@
 use anyhow::anyhow;
+use anyhow::bail;
 use itertools::Itertools;
 
 use anyhow::Error as AnyError;
 use anyhow::Result as AnyResult;
 
-use std::collections::HashMap;
+use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Write;
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
+use std::str::FromStr;
 
 use clap::Args;
 
+use crate::parallel;
+use crate::part::{Answers, Part};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+pub mod explain;
+pub mod poker;
+pub mod rules;
+pub mod signature;
+
+use explain::ExplainFormat;
+use poker::{PokerType, HAND_SIZE, SUITS};
+use rules::{Rules, WildRank, CARDS};
+use signature::{Classifier, HandType, Ranking, Signature};
+
 #[derive(Args, Debug)]
 pub struct CommandSevenArgs {
-    file: String,
+    pub file: String,
+
+    /// Parts to solve
+    #[clap(long, value_enum, default_value_t = Part::Both)]
+    pub part: Part,
+
+    /// Card alphabet, strongest first
+    #[clap(long, default_value = CARDS)]
+    pub cards: String,
+
+    /// Cards that act as wildcards, e.g. `J2`. Defaults to `J` for part two
+    /// and to none for part one.
+    #[clap(long)]
+    pub wild: Option<String>,
+
+    /// Where wildcards rank when breaking ties
+    #[clap(long, value_enum, default_value_t = WildRank::Low)]
+    pub wild_rank: WildRank,
+
+    /// Order of hand types: `lexicographic`, or signatures strongest first,
+    /// e.g. `5,41,32,311,221,2111,11111`
+    #[clap(long, default_value = "lexicographic")]
+    pub ranking: Ranking,
 
-    #[clap(long, short = '2', action)]
-    two: bool,
+    /// Play standard poker: hands are five suited cards such as
+    /// `AS KD QH JC TS`, with straights and flushes. There are no wildcards
+    /// and no second part, so `--part both` only solves part one.
+    #[clap(long, action, conflicts_with_all = ["wild", "wild_rank", "ranking"])]
+    pub poker: bool,
+
+    /// List every hand in rank order with its type, wildcard substitution,
+    /// deciding card and contribution
+    #[clap(long, action)]
+    pub explain: bool,
+
+    /// Format of the explanation. A CSV explanation is for a single part,
+    /// and the answer then goes to stderr so that stdout is one CSV table.
+    #[clap(long, value_enum, default_value_t = ExplainFormat::Table)]
+    pub format: ExplainFormat,
+
+    /// Worker threads for the parallel loops. Needs the `parallel` feature,
+    /// and defaults to the number of CPUs.
+    #[clap(long)]
+    pub threads: Option<usize>,
 }
 
-const CARDS: &str = "AKQJT98765432";
+impl CommandSevenArgs {
+    /// Rules for one part of the puzzle
+    pub fn rules(&self, two: bool) -> AnyResult<Rules> {
+        if self.poker {
+            if self.wild.is_some() || self.wild_rank != WildRank::Low {
+                bail!("Wildcards are not supported in poker mode");
+            }
+            if self.ranking != Ranking::Lexicographic {
+                bail!("Poker hands have a fixed ranking");
+            }
+            if two {
+                bail!("Poker mode only has one part");
+            }
+            return Rules::new(&self.cards, "", self.wild_rank);
+        }
+        let default_wild = if two { "J" } else { "" };
+        Rules::new(
+            &self.cards,
+            self.wild.as_deref().unwrap_or(default_wild),
+            self.wild_rank,
+        )
+    }
+}
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Card {
-    rank: u32,
-    val: char,
+pub struct Card {
+    pub rank: u32,
+    pub val: char,
+    pub wild: bool,
+    pub suit: Option<char>,
+}
+
+/// The card as written in the input: its value, then its suit if it has one
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char(self.val)?;
+        match self.suit {
+            Some(suit) => f.write_char(suit),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Debug for Card {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_char(self.val)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
     }
 }
 
 impl Card {
-    fn new(val: char, j_as_joker: bool) -> Option<Self> {
-        let index = CARDS.find(val)?;
-        Some(Card{
-            val: val,
-            rank: match val {
-                'J' if j_as_joker => 0,
-                _ => 100 - index as u32
-            }
+    pub fn new(val: char, rules: &Rules) -> Option<Self> {
+        Some(Card {
+            val,
+            rank: rules.rank(val)?,
+            wild: rules.is_wild(val),
+            suit: None,
+        })
+    }
+
+    /// A card written as its value followed by its suit, e.g. `AS`
+    pub fn suited(s: &str, rules: &Rules) -> Option<Self> {
+        let mut chars = s.chars();
+        let (val, suit) = (chars.next()?, chars.next()?);
+        if chars.next().is_some() || !SUITS.contains(suit) {
+            return None;
+        }
+        Some(Card {
+            suit: Some(suit),
+            ..Card::new(val, rules)?
         })
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
-    FiveOfAKind = 0,
-    FourOfAKind = 1,
-    FullHouse = 2,
-    ThreeOfAKind = 3,
-    TwoPair = 4,
-    OnePair = 5,
-    HighCard = 6,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Category {
+    Camel(HandType),
+    Poker(PokerType),
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Category::Camel(hand_type) => match hand_type.signature.name() {
+                Some(name) => f.write_str(name),
+                None => write!(f, "{}", hand_type.signature),
+            },
+            Category::Poker(poker_type) => write!(f, "{}", poker_type),
+        }
+    }
 }
 
-struct Hand {
-    cards: Vec<Card>,
-    points: u32,
-    j_as_joker: bool,
+/// Packs a strength followed by ranks of `bits` bits each into one integer
+/// that orders the same way as comparing them one after another
+fn pack_key(strength: u32, ranks: impl IntoIterator<Item = u32>, bits: u32) -> Option<u128> {
+    let shift = 1u128 << bits;
+    ranks.into_iter().try_fold(strength as u128, |key, rank| {
+        Some(key.checked_mul(shift)? + rank as u128)
+    })
+}
+
+/// A line of the input: the cards as written and what was bid on them
+pub struct Bid {
+    pub cards: String,
+    pub points: u32,
+}
+
+impl FromStr for Bid {
+    type Err = AnyError;
+
+    /// `32T3K 765`, or `AS KD QH JC TS 765` in poker mode
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((cards, points)) = s.trim().rsplit_once(' ') else {
+            bail!("Missing bid in `{}`", s);
+        };
+        Ok(Bid {
+            cards: cards.trim().to_string(),
+            points: points.parse::<u32>()?,
+        })
+    }
+}
+
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub points: u32,
+    pub category: Category,
+    /// Strength followed by the ranks that break ties, so hands sort by this
+    /// alone
+    key: u128,
 }
 
 impl Hand {
-    fn from_str(s: &str, j_as_joker: bool) -> Result<Self, AnyError> {
-        let split_line: Vec<&str> = s.split(" ").collect();
-        let points = split_line[1].parse::<u32>()?;
-        let cards = split_line[0].chars()
-            .map(|c| Card::new(c, j_as_joker).ok_or(anyhow!("")))
-            .collect::<Result<Vec<Card>, AnyError>>()?;
-        Ok(Hand { cards: cards, points: points, j_as_joker: j_as_joker })
+    pub fn from_str(s: &str, rules: &Rules, classifier: &mut Classifier) -> Result<Self, AnyError> {
+        Hand::new(&s.parse()?, rules, classifier)
     }
 
-    fn get_type(&self) -> HandType {
-        let mut map: HashMap<char, u32> = self.cards
-            .iter()
-            .into_grouping_map_by(|&x| x.val)
-            .fold(0, |acc, _key, _value| acc + 1);
-
-        let mut joker_count: u32 = 0;
-        if self.j_as_joker {
-            if let Some(count) = map.get(&'J') {
-                joker_count = *count;
-                map.remove(&'J');
-            }
+    pub fn new(bid: &Bid, rules: &Rules, classifier: &mut Classifier) -> Result<Self, AnyError> {
+        let cards = bid
+            .cards
+            .chars()
+            .map(|c| Card::new(c, rules).ok_or_else(|| anyhow!("Unknown card `{}`", c)))
+            .collect::<Result<Vec<Card>, AnyError>>()?;
+        // classifying takes time and memory growing quickly with the number of
+        // cards, so give up early on hands that could not be sorted anyway
+        if pack_key(1, cards.iter().map(|c| c.rank), rules.rank_bits()).is_none() {
+            bail!("Hand {} is too long to sort", bid.cards);
         }
 
-        let mut counts: Vec<u32> = map
-            .values()
-            .sorted()
-            .rev()
-            .map(|&x| x)
-            .collect();
+        let natural = Signature::of(cards.iter().filter(|c| !c.wild).map(|c| c.val));
+        let wild = cards.iter().filter(|c| c.wild).count() as u32;
+        let hand_type = classifier
+            .classify(natural, wild)
+            .ok_or_else(|| anyhow!("Hand {} has no type in the ranking", bid.cards))?;
+
+        let key = pack_key(
+            hand_type.strength,
+            cards.iter().map(|c| c.rank),
+            rules.rank_bits(),
+        )
+        .ok_or_else(|| anyhow!("Hand {} is too long to sort", bid.cards))?;
+
+        Ok(Hand {
+            cards,
+            points: bid.points,
+            category: Category::Camel(hand_type),
+            key,
+        })
+    }
 
-        // this can only happen if they are all jokers!
-        if counts.len() == 0 {
-            return HandType::FiveOfAKind
+    pub fn from_poker_str(s: &str, rules: &Rules) -> Result<Self, AnyError> {
+        Hand::poker(&s.parse()?, rules)
+    }
+
+    /// A poker hand, with cards such as `AS KD QH JC TS`
+    pub fn poker(bid: &Bid, rules: &Rules) -> Result<Self, AnyError> {
+        let cards = bid
+            .cards
+            .split_whitespace()
+            .map(|c| Card::suited(c, rules).ok_or_else(|| anyhow!("Unknown card `{}`", c)))
+            .collect::<Result<Vec<Card>, AnyError>>()?;
+        if cards.len() != HAND_SIZE {
+            bail!(
+                "Poker hand `{}` does not have {} cards",
+                bid.cards,
+                HAND_SIZE
+            );
         }
-        counts[0] += joker_count;
-
-        match counts.iter().join("").as_str() {
-            "5" => HandType::FiveOfAKind,
-            "41" => HandType::FourOfAKind,
-            "32" => HandType::FullHouse,
-            "311" => HandType::ThreeOfAKind,
-            "221" => HandType::TwoPair,
-            "2111" => HandType::OnePair,
-            _ => HandType::HighCard,
+        if let Some(card) = cards.iter().duplicates().next() {
+            bail!("Card {} appears more than once in `{}`", card, bid.cards);
         }
+
+        let (lowest, highest) = rules.rank_bounds();
+        let (poker_type, mut ranks) = poker::classify(&cards, lowest, highest);
+        // pad so that ranks line up between hands of the same type
+        ranks.resize(HAND_SIZE, 0);
+        let key = pack_key(poker_type as u32, ranks, rules.rank_bits())
+            .ok_or_else(|| anyhow!("Hand `{}` is too long to sort", bid.cards))?;
+
+        Ok(Hand {
+            cards,
+            points: bid.points,
+            category: Category::Poker(poker_type),
+            key,
+        })
     }
 }
 
@@ -118,7 +289,7 @@ impl Eq for Hand {}
 
 impl PartialEq for Hand {
     fn eq(&self, other: &Self) -> bool {
-        return self.cmp(other) == std::cmp::Ordering::Equal
+        self.cmp(other) == std::cmp::Ordering::Equal
     }
 }
 
@@ -129,14 +300,13 @@ impl PartialOrd for Hand {
 }
 
 impl Ord for Hand {
+    /// Strengths only compare between hands with as many cards, so longer
+    /// hands rank higher whatever their type
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let mut cmp = self.get_type().cmp(&other.get_type()).reverse();
-        let mut card_idx = 0;
-        while cmp == std::cmp::Ordering::Equal && card_idx < self.cards.len() {
-            cmp = self.cards[card_idx].cmp(&other.cards[card_idx]);
-            card_idx += 1;
-        }
-        cmp
+        self.cards
+            .len()
+            .cmp(&other.cards.len())
+            .then(self.key.cmp(&other.key))
     }
 }
 
@@ -145,56 +315,508 @@ impl Debug for Hand {
         f.debug_struct("Hand")
             .field("cards", &self.cards.iter().map(|c| c.val).join(""))
             .field("points", &self.points)
-            .field("hand", &self.get_type())
+            .field("hand", &self.category)
             .finish()
     }
 }
 
-pub fn run(args: &CommandSevenArgs) -> AnyResult<u64> {
-    let file = File::open(args.file.as_str()).expect("Should have been able to read the file");
+pub fn parse(lines: impl Iterator<Item = String>) -> AnyResult<Vec<Bid>> {
+    lines.map(|l| l.parse::<Bid>()).collect()
+}
+
+/// Hands of `bids`, weakest first. Every worker thread makes its own state
+/// with `init`, so that a classifier cache needs no locking.
+fn sorted_hands<S>(
+    bids: &[Bid],
+    init: impl Fn() -> S + Sync + Send,
+    hand: impl Fn(&mut S, &Bid) -> AnyResult<Hand> + Sync + Send,
+) -> AnyResult<Vec<Hand>> {
+    #[cfg(feature = "parallel")]
+    let mut hands = bids
+        .par_iter()
+        .map_init(init, hand)
+        .collect::<Result<Vec<Hand>, AnyError>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let mut hands = {
+        let mut state = init();
+        bids.iter()
+            .map(|bid| hand(&mut state, bid))
+            .collect::<Result<Vec<Hand>, AnyError>>()?
+    };
+
+    // strengths only mean something between hands with as many cards
+    if let Some(i) = (1..hands.len()).find(|&i| hands[i].cards.len() != hands[0].cards.len()) {
+        bail!(
+            "Hand {} has {} cards but hand {} has {}",
+            bids[i].cards,
+            hands[i].cards.len(),
+            bids[0].cards,
+            hands[0].cards.len()
+        );
+    }
+
+    // stable, so that equal hands keep their input order
+    #[cfg(feature = "parallel")]
+    hands.par_sort();
+    #[cfg(not(feature = "parallel"))]
+    hands.sort();
+    Ok(hands)
+}
+
+/// Camel Cards hands under `rules`, weakest first
+pub fn ranked(bids: &[Bid], rules: &Rules, ranking: &Ranking) -> AnyResult<Vec<Hand>> {
+    sorted_hands(
+        bids,
+        || Classifier::new(ranking),
+        |classifier, bid| Hand::new(bid, rules, classifier),
+    )
+}
+
+/// Poker hands under `rules`, weakest first
+pub fn ranked_poker(bids: &[Bid], rules: &Rules) -> AnyResult<Vec<Hand>> {
+    sorted_hands(bids, || (), |_, bid| Hand::poker(bid, rules))
+}
+
+/// Sum of every bid times the rank of its hand
+pub fn winnings(hands: &[Hand]) -> u64 {
+    let mut points: u64 = 0;
+    for (i, h) in hands.iter().enumerate() {
+        points += ((i + 1) as u64) * h.points as u64;
+    }
+    points
+}
+
+/// Winnings with the usual cards and no wildcards
+pub fn part_one(bids: &[Bid]) -> AnyResult<u64> {
+    let rules = Rules::new(CARDS, "", WildRank::Low)?;
+    Ok(winnings(&ranked(bids, &rules, &Ranking::Lexicographic)?))
+}
+
+/// Winnings with jokers as the weakest wildcards
+pub fn part_two(bids: &[Bid]) -> AnyResult<u64> {
+    let rules = Rules::new(CARDS, "J", WildRank::Low)?;
+    Ok(winnings(&ranked(bids, &rules, &Ranking::Lexicographic)?))
+}
+
+pub fn run(args: &CommandSevenArgs) -> AnyResult<Answers<u64>> {
+    let file =
+        File::open(&args.file).map_err(|e| anyhow!("Could not read {}: {}", args.file, e))?;
     let lines = io::BufReader::new(file)
         .lines()
-        .filter_map(|s| s.ok())
-        .into_iter();
-    
-    if !args.two {
-        let hands: Vec<Hand> = lines.map(|l| Hand::from_str(&l, false)).collect::<Result<Vec<Hand>, AnyError>>()?;
-        let mut points: u64 = 0;
-        for (i, h) in hands.iter().sorted().enumerate() {
-            points += ((i+1) as u64) * h.points as u64;
-        }
-        println!("Answer: {}", points);
-        Ok(points)
-    } else {
-        let hands: Vec<Hand> = lines.map(|l| Hand::from_str(&l, true)).collect::<Result<Vec<Hand>, AnyError>>()?;
-        let mut points: u64 = 0;
-        for (i, h) in hands.iter().sorted().enumerate() {
-            points += ((i+1) as u64) * h.points as u64;
+        .collect::<io::Result<Vec<String>>>()?;
+    let bids = parse(lines.into_iter())?;
+    let part = match (args.poker, args.part) {
+        (true, Part::Both) => Part::One,
+        _ => args.part,
+    };
+    let csv = args.explain && args.format == ExplainFormat::Csv;
+    if csv && part == Part::Both {
+        bail!("A CSV explanation needs a single part, use --part 1 or --part 2");
+    }
+    let solve = |two: bool| -> AnyResult<u64> {
+        let rules = args.rules(two)?;
+        let hands = match args.poker {
+            true => ranked_poker(&bids, &rules)?,
+            false => ranked(&bids, &rules, &args.ranking)?,
+        };
+        if args.explain {
+            let sorted: Vec<&Hand> = hands.iter().collect();
+            println!(
+                "{}",
+                explain::render(&explain::explain(&sorted, &rules), args.format)
+            );
         }
-        println!("Answer: {}", points);
-        Ok(points)
+        Ok(winnings(&hands))
+    };
+    let answers = parallel::install(args.threads, || {
+        Answers::solve(part, || solve(false), || solve(true))
+    })??;
+    match csv {
+        true => eprintln!("{}", answers),
+        false => println!("{}", answers),
     }
+    Ok(answers)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::time::Instant;
+
+    use crate::day::Day;
+    use crate::generate::generate;
+
     #[test]
     fn test_input() {
         let r = self::run(&CommandSevenArgs {
             file: "./inputs/seven_test.txt".to_string(),
-            two: false,
+            part: Part::One,
+            cards: CARDS.to_string(),
+            wild: None,
+            wild_rank: WildRank::Low,
+            ranking: Ranking::Lexicographic,
+            poker: false,
+            explain: false,
+            format: ExplainFormat::Table,
+            threads: None,
         });
-        assert_eq!(r.unwrap(), 6440);
+        assert_eq!(r.unwrap().one, Some(6440));
     }
 
     #[test]
     fn test_input_part2() {
         let r = self::run(&CommandSevenArgs {
             file: "./inputs/seven_test.txt".to_string(),
-            two: true,
+            part: Part::Two,
+            cards: CARDS.to_string(),
+            wild: None,
+            wild_rank: WildRank::Low,
+            ranking: Ranking::Lexicographic,
+            poker: false,
+            explain: false,
+            format: ExplainFormat::Table,
+            threads: None,
+        });
+        assert_eq!(r.unwrap().two, Some(5905));
+    }
+
+    #[test]
+    fn test_csv_explanation() {
+        let args = CommandSevenArgs {
+            file: "./inputs/seven_test.txt".to_string(),
+            part: Part::Both,
+            cards: CARDS.to_string(),
+            wild: None,
+            wild_rank: WildRank::Low,
+            ranking: Ranking::Lexicographic,
+            poker: false,
+            explain: true,
+            format: ExplainFormat::Csv,
+            threads: None,
+        };
+        assert!(run(&args).is_err());
+        let args = CommandSevenArgs {
+            part: Part::Two,
+            ..args
+        };
+        assert_eq!(run(&args).unwrap().two, Some(5905));
+
+        // poker mode only has the one part
+        let args = CommandSevenArgs {
+            file: "./inputs/seven_poker_test.txt".to_string(),
+            part: Part::Both,
+            poker: true,
+            ..args
+        };
+        assert!(run(&args).unwrap().one.is_some());
+    }
+
+    #[test]
+    fn test_both_parts() {
+        let bids = parse(
+            std::fs::read_to_string("./inputs/seven_test.txt")
+                .unwrap()
+                .lines()
+                .map(|l| l.to_string()),
+        )
+        .unwrap();
+        let args = CommandSevenArgs {
+            file: "./inputs/seven_test.txt".to_string(),
+            part: Part::Both,
+            cards: CARDS.to_string(),
+            wild: None,
+            wild_rank: WildRank::Low,
+            ranking: Ranking::Lexicographic,
+            poker: false,
+            explain: false,
+            format: ExplainFormat::Table,
+            threads: None,
+        };
+        assert_eq!(part_one(&bids).unwrap(), 6440);
+        assert_eq!(part_two(&bids).unwrap(), 5905);
+        assert_eq!(
+            run(&args).unwrap(),
+            Answers {
+                one: Some(6440),
+                two: Some(5905)
+            }
+        );
+
+        assert!(parse(["32T3K".to_string()].into_iter()).is_err());
+        assert!(parse(["32T3K x".to_string()].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let args = CommandSevenArgs {
+            file: "./inputs/seven_test.txt".to_string(),
+            part: Part::One,
+            cards: CARDS.to_string(),
+            wild: Some("J".to_string()),
+            wild_rank: WildRank::Low,
+            ranking: Ranking::Lexicographic,
+            poker: false,
+            explain: false,
+            format: ExplainFormat::Table,
+            threads: None,
+        };
+        assert_eq!(run(&args).unwrap().one, Some(5905));
+
+        let args = CommandSevenArgs {
+            wild: Some("J5".to_string()),
+            wild_rank: WildRank::High,
+            ..args
+        };
+        assert_eq!(run(&args).unwrap().one, Some(6570));
+
+        let args = CommandSevenArgs {
+            wild: Some("X".to_string()),
+            ..args
+        };
+        assert!(run(&args).is_err());
+    }
+
+    #[test]
+    fn test_custom_ranking() {
+        let args = CommandSevenArgs {
+            file: "./inputs/seven_test.txt".to_string(),
+            part: Part::One,
+            cards: CARDS.to_string(),
+            wild: None,
+            wild_rank: WildRank::Low,
+            ranking: "5,41,32,311,221,2111,11111".parse().unwrap(),
+            poker: false,
+            explain: false,
+            format: ExplainFormat::Table,
+            threads: None,
+        };
+        assert_eq!(run(&args).unwrap().one, Some(6440));
+
+        // two pair beats three of a kind
+        let args = CommandSevenArgs {
+            ranking: "5,41,32,221,311,2111,11111".parse().unwrap(),
+            ..args
+        };
+        assert_eq!(
+            run(&args).unwrap().one.unwrap(),
+            765 + 2 * 684 + 3 * 483 + 4 * 220 + 5 * 28
+        );
+
+        let args = CommandSevenArgs {
+            ranking: "5,41,32,311,221".parse().unwrap(),
+            ..args
+        };
+        assert!(run(&args).is_err());
+    }
+
+    #[test]
+    fn test_mixed_sizes() {
+        let bids = parse(["AAAAA 1", "23456K 2"].into_iter().map(String::from)).unwrap();
+        assert_eq!(
+            part_one(&bids).unwrap_err().to_string(),
+            "Hand 23456K has 6 cards but hand AAAAA has 5"
+        );
+        assert!(part_two(&bids).is_err());
+
+        let bids = parse(["AAAAAA 10", "23456K 20"].into_iter().map(String::from)).unwrap();
+        assert_eq!(part_one(&bids).unwrap(), 20 + 2 * 10);
+    }
+
+    #[test]
+    fn test_wild_hand_type() {
+        let rules = Rules::new(CARDS, "J2", WildRank::Low).unwrap();
+        let ranking = Ranking::Lexicographic;
+        let mut classifier = Classifier::new(&ranking);
+        let mut hand = |s: &str| match Hand::from_str(s, &rules, &mut classifier).unwrap().category
+        {
+            Category::Camel(hand_type) => hand_type.signature.to_string(),
+            Category::Poker(_) => unreachable!(),
+        };
+        assert_eq!(hand("J2345 1"), "3+1+1");
+        assert_eq!(hand("JJ22A 1"), "5");
+        assert_eq!(hand("2KK33 1"), "3+2");
+        assert_eq!(hand("AKQT9 1"), "1+1+1+1+1");
+        assert_eq!(hand("JJJJJ2 1"), "6");
+        assert_eq!(hand("AAKKQQ9 1"), "2+2+2+1");
+    }
+
+    #[test]
+    fn test_poker() {
+        let args = CommandSevenArgs {
+            file: "./inputs/seven_poker_test.txt".to_string(),
+            part: Part::One,
+            cards: CARDS.to_string(),
+            wild: None,
+            wild_rank: WildRank::Low,
+            ranking: Ranking::Lexicographic,
+            poker: true,
+            explain: false,
+            format: ExplainFormat::Table,
+            threads: None,
+        };
+        assert_eq!(
+            run(&args).unwrap().one.unwrap(),
+            60 + 2 * 50 + 3 * 40 + 4 * 30 + 5 * 20 + 6 * 10
+        );
+
+        let args = CommandSevenArgs {
+            part: Part::Both,
+            ..args
+        };
+        assert_eq!(run(&args).unwrap().two, None);
+        let args = CommandSevenArgs {
+            part: Part::Two,
+            ..args
+        };
+        assert!(run(&args).is_err());
+
+        let args = CommandSevenArgs {
+            part: Part::One,
+            wild: Some("J".to_string()),
+            ..args
+        };
+        assert!(run(&args).is_err());
+        let args = CommandSevenArgs {
+            wild: None,
+            wild_rank: WildRank::High,
+            ..args
+        };
+        assert!(run(&args).is_err());
+        let args = CommandSevenArgs {
+            wild_rank: WildRank::Low,
+            ranking: "5,41,32,311,221,2111,11111".parse().unwrap(),
+            ..args
+        };
+        assert!(run(&args).is_err());
+
+        let command = CommandSevenArgs::augment_args(clap::Command::new("seven"));
+        for flag in [
+            "--wild=J",
+            "--wild-rank=high",
+            "--ranking=5,41,32,311,221,2111,11111",
+        ] {
+            let matches =
+                command
+                    .clone()
+                    .try_get_matches_from(["seven", "hands.txt", "--poker", flag]);
+            assert!(matches.is_err());
+        }
+    }
+
+    #[test]
+    fn test_poker_kickers() {
+        let rules = Rules::new(CARDS, "", WildRank::Low).unwrap();
+        let hand = |s: &str| Hand::from_poker_str(&format!("{} 1", s), &rules).unwrap();
+
+        // higher trips win a full house, whatever the pair
+        assert!(hand("3C 3D 3H 2S 2C") > hand("2D 2H 2C AS AD"));
+        // higher pair first, then the second pair, then the kicker
+        assert!(hand("KC KD 2H 2S 3C") > hand("QC QD JH JS AC"));
+        assert!(hand("KC KD 4H 4S 2C") > hand("KH KS 3H 3S AC"));
+        assert!(hand("KC KD 4H 4S 3C") > hand("KH KS 4D 4C 2D"));
+        // flushes compare every card
+        assert!(hand("AH JH 9H 5H 3H") > hand("AS JS 9S 5S 2S"));
+        // ties ignore suits and card order
+        assert!(hand("AH JH 9D 5H 3H") == hand("3S 5S 9S JC AS"));
+        // the wheel is the lowest straight
+        assert!(hand("6C 5D 4H 3S 2C") > hand("AC 2D 3H 4S 5C"));
+        assert!(hand("AC 2D 3H 4S 5C") > hand("AC AD KH QS JC"));
+
+        let bad = |s: &str| Hand::from_poker_str(s, &rules).is_err();
+        assert!(bad("AS KS QS JS 1"));
+        assert!(bad("AS KS QS JS TS 9S 1"));
+        assert!(bad("AS AS QS JS TS 1"));
+        assert!(bad("AX KS QS JS TS 1"));
+        assert!(bad("AS KS QS JS TS"));
+    }
+
+    #[test]
+    fn test_sort_key() {
+        let rules = Rules::new(CARDS, "J", WildRank::Low).unwrap();
+        let ranking = Ranking::Lexicographic;
+        let mut classifier = Classifier::new(&ranking);
+        let mut hand = |s: &str| Hand::from_str(s, &rules, &mut classifier).unwrap();
+
+        assert!(hand("AAAAA 1") > hand("JJJJJ 1"));
+        assert!(hand("JJJJJ 1") > hand("AAAAK 1"));
+        assert!(hand("2345J 1") > hand("AKQT9 1"));
+        assert!(hand("KTJ9T 1") > hand("KK677 1"));
+        assert!(hand("QQQJA 1") == hand("QQQJA 2"));
+        assert!(hand("23456K 1") > hand("AAAAA 1"));
+        assert!(hand("AAAA 1") < hand("23456 1"));
+
+        // 13 cards need 5 bits each, so 25 cards plus the strength overflow
+        let long = format!("{} 1", "A".repeat(25));
+        assert!(Hand::from_str(&long, &rules, &mut classifier).is_err());
+        // found by fuzzing: rejected before classifying its many cards
+        let long = format!("{}22 2", "K".repeat(90));
+        assert!(Hand::from_str(&long, &rules, &mut classifier).is_err());
+    }
+
+    fn generate_hands(count: usize) -> Vec<String> {
+        generate(Day::Seven, count, 0)
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_sort_hands() {
+        let rules = Rules::new(CARDS, "J", WildRank::Low).unwrap();
+        let ranking = Ranking::Lexicographic;
+        let lines = generate_hands(1_000_000);
+
+        let start = Instant::now();
+        let mut classifier = Classifier::new(&ranking);
+        let mut hands: Vec<Hand> = lines
+            .iter()
+            .map(|l| Hand::from_str(l, &rules, &mut classifier).unwrap())
+            .collect();
+        println!("parse {} hands: {:?}", hands.len(), start.elapsed());
+
+        let start = Instant::now();
+        hands.sort();
+        println!("sort by key: {:?}", start.elapsed());
+
+        // the old approach, classifying both hands on every comparison
+        let classify = |h: &Hand| {
+            let natural = Signature::of(h.cards.iter().filter(|c| !c.wild).map(|c| c.val));
+            let wild = h.cards.iter().filter(|c| c.wild).count() as u32;
+            ranking.strength(&ranking.best_with_wildcards(&natural, wild).unwrap())
+        };
+        let mut sample: Vec<&Hand> = hands.iter().step_by(10).collect();
+        sample.reverse();
+        let start = Instant::now();
+        sample.sort_by(|a, b| {
+            classify(a)
+                .cmp(&classify(b))
+                .then_with(|| a.cards.cmp(&b.cards))
         });
-        assert_eq!(r.unwrap(), 5905);
+        println!(
+            "sort {} hands reclassifying on every comparison: {:?}",
+            sample.len(),
+            start.elapsed()
+        );
+        assert!(sample.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_parallel() {
+        let bids = parse(generate_hands(1_000_000).into_iter()).unwrap();
+        let mut answers = vec![];
+        for threads in parallel::bench_threads() {
+            let start = Instant::now();
+            answers.push(parallel::install(threads, || part_two(&bids).unwrap()).unwrap());
+            println!(
+                "rank {} hands, {:?} threads: {:?}",
+                bids.len(),
+                threads,
+                start.elapsed()
+            );
+        }
+        assert!(answers.iter().all_equal());
     }
 }
@@ -0,0 +1,287 @@
+use clap::ValueEnum;
+use itertools::Itertools;
+
+use super::rules::Rules;
+use super::signature::HandType;
+use super::{Category, Hand};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ExplainFormat {
+    Table,
+    Csv,
+}
+
+/// One line of the explanation, for the hand at `rank` (1 is the weakest)
+#[derive(Debug, PartialEq)]
+pub struct Explanation {
+    rank: usize,
+    cards: String,
+    hand_type: String,
+    substitution: String,
+    decided_by: String,
+    points: u32,
+    contribution: u64,
+}
+
+/// What each wildcard of the hand stands for, in the order they appear
+fn substitution(hand: &Hand, hand_type: &HandType, rules: &Rules) -> Vec<(char, char)> {
+    let wilds: Vec<char> = hand
+        .cards
+        .iter()
+        .filter(|c| c.wild)
+        .map(|c| c.val)
+        .collect();
+    if wilds.is_empty() {
+        return vec![];
+    }
+
+    // natural groups, largest first and stronger cards first among groups of
+    // the same size
+    let mut groups: Vec<(u32, u32, char)> = hand
+        .cards
+        .iter()
+        .filter(|c| !c.wild)
+        .map(|c| (c.rank, c.val))
+        .counts()
+        .into_iter()
+        .map(|((rank, val), count)| (count as u32, rank, val))
+        .sorted()
+        .rev()
+        .collect();
+    let mut fresh = rules
+        .cards()
+        .iter()
+        .copied()
+        .filter(|&c| !rules.is_wild(c) && !hand.cards.iter().any(|card| card.val == c));
+
+    // give each group of the final signature the largest natural group that
+    // still fits it, topped up with wildcards; groups nothing fits are made of
+    // wildcards alone
+    let mut targets: Vec<char> = vec![];
+    for &part in hand_type.signature.counts() {
+        let (count, val) = match groups.iter().position(|g| g.0 <= part) {
+            Some(i) => {
+                let (count, _, val) = groups.remove(i);
+                (count, val)
+            }
+            None => (0, fresh.next().unwrap_or(wilds[0])),
+        };
+        targets.extend(std::iter::repeat_n(val, (part - count) as usize));
+    }
+    wilds.into_iter().zip(targets).collect()
+}
+
+/// Why `hand` ranks above `previous`, the hand just below it
+fn decided_by(hand: &Hand, previous: &Hand, rules: &Rules) -> String {
+    let bits = rules.rank_bits();
+    let n = hand.cards.len() as u32;
+    let chunk = |key: u128, i: u32| (key >> ((n - 1 - i) * bits)) & ((1 << bits) - 1);
+    if hand.key >> (n * bits) != previous.key >> (n * bits) {
+        return "type".to_string();
+    }
+    let position = match hand.category {
+        Category::Camel(_) => "card",
+        Category::Poker(_) => "kicker",
+    };
+    (0..n)
+        .find(|&i| chunk(hand.key, i) != chunk(previous.key, i))
+        .map(|i| {
+            let name = |key| {
+                rules
+                    .card(chunk(key, i) as u32)
+                    .map_or("-".to_string(), |c| c.to_string())
+            };
+            format!(
+                "{} {}: {} > {}",
+                position,
+                i + 1,
+                name(hand.key),
+                name(previous.key)
+            )
+        })
+        .unwrap_or("tie".to_string())
+}
+
+/// Explains every hand, weakest first. `hands` must be sorted.
+pub fn explain(hands: &[&Hand], rules: &Rules) -> Vec<Explanation> {
+    hands
+        .iter()
+        .enumerate()
+        .map(|(i, hand)| {
+            let substitution = match &hand.category {
+                Category::Camel(hand_type) => substitution(hand, hand_type, rules),
+                Category::Poker(_) => vec![],
+            };
+            Explanation {
+                rank: i + 1,
+                cards: match hand.category {
+                    Category::Camel(_) => hand.cards.iter().map(|c| c.val).join(""),
+                    Category::Poker(_) => hand.cards.iter().join(" "),
+                },
+                hand_type: hand.category.to_string(),
+                substitution: match substitution.is_empty() {
+                    true => "-".to_string(),
+                    false => format!(
+                        "{}→{}",
+                        substitution.iter().map(|s| s.0).join(""),
+                        substitution.iter().map(|s| s.1).join("")
+                    ),
+                },
+                decided_by: match i {
+                    0 => "-".to_string(),
+                    _ => decided_by(hand, hands[i - 1], rules),
+                },
+                points: hand.points,
+                contribution: (i + 1) as u64 * hand.points as u64,
+            }
+        })
+        .collect()
+}
+
+/// A CSV field, quoted as in RFC 4180 when it holds a separator, a quote or a
+/// line break
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\r', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+pub fn render(explanations: &[Explanation], format: ExplainFormat) -> String {
+    let header = [
+        "rank",
+        "hand",
+        "type",
+        "wildcards",
+        "decided by",
+        "points",
+        "contribution",
+    ];
+    let rows = explanations.iter().map(|e| {
+        [
+            e.rank.to_string(),
+            e.cards.clone(),
+            e.hand_type.clone(),
+            e.substitution.clone(),
+            e.decided_by.clone(),
+            e.points.to_string(),
+            e.contribution.to_string(),
+        ]
+    });
+    match format {
+        ExplainFormat::Csv => std::iter::once(header.map(|h| h.to_string()))
+            .chain(rows)
+            .map(|row| row.iter().map(|field| csv_field(field)).join(","))
+            .join("\n"),
+        ExplainFormat::Table => {
+            let mut out = format!(
+                "{:>6}  {:<15} {:<16} {:<12} {:<18} {:>8} {:>12}",
+                header[0], header[1], header[2], header[3], header[4], header[5], header[6]
+            );
+            for row in rows {
+                out += &format!(
+                    "\n{:>6}  {:<15} {:<16} {:<12} {:<18} {:>8} {:>12}",
+                    row[0], row[1], row[2], row[3], row[4], row[5], row[6]
+                );
+            }
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rules::{WildRank, CARDS};
+    use super::super::signature::{Classifier, Ranking};
+    use super::*;
+
+    fn explain_lines(lines: &[&str], rules: &Rules, ranking: &Ranking) -> Vec<Explanation> {
+        let mut classifier = Classifier::new(ranking);
+        let hands: Vec<Hand> = lines
+            .iter()
+            .map(|l| Hand::from_str(l, rules, &mut classifier).unwrap())
+            .sorted()
+            .collect();
+        explain(&hands.iter().collect::<Vec<&Hand>>(), rules)
+    }
+
+    #[test]
+    fn sample() {
+        let rules = Rules::new(CARDS, "J", WildRank::Low).unwrap();
+        let lines = std::fs::read_to_string("./inputs/seven_test.txt").unwrap();
+        let explanations = explain_lines(
+            &lines.lines().collect::<Vec<_>>(),
+            &rules,
+            &Ranking::Lexicographic,
+        );
+        assert_eq!(
+            render(&explanations, ExplainFormat::Csv),
+            "rank,hand,type,wildcards,decided by,points,contribution
+1,32T3K,one pair,-,-,765,765
+2,KK677,two pair,-,type,28,56
+3,T55J5,four of a kind,J→5,type,684,2052
+4,QQQJA,four of a kind,J→Q,card 1: Q > T,483,1932
+5,KTJJT,four of a kind,JJ→TT,card 1: K > Q,220,1100"
+        );
+        assert_eq!(
+            explanations.iter().map(|e| e.contribution).sum::<u64>(),
+            5905
+        );
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("one pair"), "one pair");
+        assert_eq!(csv_field("a,b"), r#""a,b""#);
+        assert_eq!(csv_field(r#"say "hi""#), r#""say ""hi""""#);
+
+        // cards named by a comma and a quote, both strongest
+        let rules = Rules::new(r#","AKQ"#, "", WildRank::Low).unwrap();
+        let lines = [r#""QQQQ 1"#, ",QQQQ 2"];
+        let explanations = explain_lines(&lines, &rules, &Ranking::Lexicographic);
+        assert_eq!(
+            render(&explanations, ExplainFormat::Csv)
+                .lines()
+                .skip(1)
+                .collect::<Vec<_>>(),
+            [
+                r#"1,"""QQQQ",four of a kind,-,-,1,1"#,
+                r#"2,",QQQQ",four of a kind,-,"card 1: , > """,2,4"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn substitutions() {
+        let rules = Rules::new(CARDS, "J2", WildRank::Low).unwrap();
+        let wildcards = |line: &str, ranking: &Ranking| {
+            explain_lines(&[line], &rules, ranking)[0]
+                .substitution
+                .clone()
+        };
+        let lexicographic = Ranking::Lexicographic;
+        assert_eq!(wildcards("AKQJ9 1", &lexicographic), "J→A");
+        assert_eq!(wildcards("AK2J9 1", &lexicographic), "2J→AA");
+        assert_eq!(wildcards("JJ22J 1", &lexicographic), "JJ22J→AAAAA");
+        assert_eq!(wildcards("AKQT9 1", &lexicographic), "-");
+
+        // two pair beats three of a kind, so the wildcard pairs up the ace
+        // instead of joining the kings
+        let ranking: Ranking = "5,41,32,221,311,2111,11111".parse().unwrap();
+        assert_eq!(wildcards("KKAJ9 1", &ranking), "J→A");
+    }
+
+    #[test]
+    fn decided_by_kicker() {
+        let rules = Rules::new(CARDS, "", WildRank::Low).unwrap();
+        let hands: Vec<Hand> = ["KC KD 4H 4S 2C 1", "KH KS 4D 4C 3D 1", "KH KS 4D 4C 3S 1"]
+            .iter()
+            .map(|l| Hand::from_poker_str(l, &rules).unwrap())
+            .collect();
+        let explanations = explain(&hands.iter().collect::<Vec<&Hand>>(), &rules);
+        assert_eq!(explanations[1].decided_by, "kicker 3: 3 > 2");
+        assert_eq!(explanations[2].decided_by, "tie");
+        assert_eq!(explanations[2].hand_type, "two pair");
+        assert_eq!(explanations[0].cards, "KC KD 4H 4S 2C");
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+
+use itertools::Itertools;
+
+use super::Card;
+
+/// Standard poker hand categories, weakest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PokerType {
+    HighCard = 0,
+    OnePair = 1,
+    TwoPair = 2,
+    ThreeOfAKind = 3,
+    Straight = 4,
+    Flush = 5,
+    FullHouse = 6,
+    FourOfAKind = 7,
+    StraightFlush = 8,
+}
+
+impl fmt::Display for PokerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PokerType::HighCard => "high card",
+            PokerType::OnePair => "one pair",
+            PokerType::TwoPair => "two pair",
+            PokerType::ThreeOfAKind => "three of a kind",
+            PokerType::Straight => "straight",
+            PokerType::Flush => "flush",
+            PokerType::FullHouse => "full house",
+            PokerType::FourOfAKind => "four of a kind",
+            PokerType::StraightFlush => "straight flush",
+        };
+        f.write_str(name)
+    }
+}
+
+pub const SUITS: &str = "SHDC";
+
+pub const HAND_SIZE: usize = 5;
+
+/// Classifies a five card hand. Returns its type and the ranks that break ties
+/// between hands of that type, most significant first: the top card of a
+/// straight, otherwise the ranks of the groups of equal cards, larger groups
+/// first and higher ranks first within groups of the same size.
+///
+/// `lowest` and `highest` are the ranks of the weakest and strongest card in
+/// the deck, needed to spot the wheel (ace to five) straight.
+pub fn classify(cards: &[Card], lowest: u32, highest: u32) -> (PokerType, Vec<u32>) {
+    let groups: Vec<(usize, u32)> = cards
+        .iter()
+        .map(|c| c.rank)
+        .counts()
+        .into_iter()
+        .map(|(rank, count)| (count, rank))
+        .sorted()
+        .rev()
+        .collect();
+    let ranks: Vec<u32> = groups.iter().map(|&(_, rank)| rank).collect();
+    let flush = cards.iter().map(|c| c.suit).all_equal();
+
+    let straight_top = match ranks[..] {
+        [top, .., bottom] if ranks.len() == HAND_SIZE && top - bottom == 4 => Some(top),
+        // the ace plays low in the wheel, topped by the five
+        [top, five, .., bottom]
+            if ranks.len() == HAND_SIZE
+                && top == highest
+                && bottom == lowest
+                && five - bottom == 3 =>
+        {
+            Some(five)
+        }
+        _ => None,
+    };
+
+    let counts: Vec<usize> = groups.iter().map(|&(count, _)| count).collect();
+    let poker_type = match (straight_top, flush, &counts[..]) {
+        (Some(_), true, _) => PokerType::StraightFlush,
+        (_, _, [4, ..]) => PokerType::FourOfAKind,
+        (_, _, [3, 2]) => PokerType::FullHouse,
+        (_, true, _) => PokerType::Flush,
+        (Some(_), _, _) => PokerType::Straight,
+        (_, _, [3, ..]) => PokerType::ThreeOfAKind,
+        (_, _, [2, 2, ..]) => PokerType::TwoPair,
+        (_, _, [2, ..]) => PokerType::OnePair,
+        _ => PokerType::HighCard,
+    };
+
+    match straight_top {
+        Some(top) => (poker_type, vec![top]),
+        None => (poker_type, ranks),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rules::{Rules, WildRank, CARDS};
+    use super::*;
+
+    fn classify_str(hand: &str) -> (PokerType, String) {
+        let rules = Rules::new(CARDS, "", WildRank::Low).unwrap();
+        let cards: Vec<Card> = hand
+            .split(' ')
+            .map(|c| Card::suited(c, &rules).unwrap())
+            .collect();
+        let (lowest, highest) = rules.rank_bounds();
+        let (poker_type, ranks) = classify(&cards, lowest, highest);
+        // map ranks back to card names to keep the expectations readable
+        let names = ranks
+            .iter()
+            .map(|&r| CARDS.chars().find(|&c| rules.rank(c) == Some(r)).unwrap())
+            .collect();
+        (poker_type, names)
+    }
+
+    #[test]
+    fn categories() {
+        let cases = [
+            ("AS KS QS JS TS", PokerType::StraightFlush, "A"),
+            ("5D 4D 3D 2D AD", PokerType::StraightFlush, "5"),
+            ("9C 9D 9H 9S 2C", PokerType::FourOfAKind, "92"),
+            ("3C 3D KH KS 3S", PokerType::FullHouse, "3K"),
+            ("2H 7H 9H JH KH", PokerType::Flush, "KJ972"),
+            ("6C 5D 4H 3S 2C", PokerType::Straight, "6"),
+            ("AC 2D 3H 4S 5C", PokerType::Straight, "5"),
+            ("KC AD QH JS TC", PokerType::Straight, "A"),
+            ("QC AD KH 3S 2C", PokerType::HighCard, "AKQ32"),
+            ("7C 7D 7H 4S 2C", PokerType::ThreeOfAKind, "742"),
+            ("4C 4D JH JS 2C", PokerType::TwoPair, "J42"),
+            ("TC TD 8H 4S 2C", PokerType::OnePair, "T842"),
+        ];
+        for (hand, poker_type, ranks) in cases {
+            assert_eq!(
+                classify_str(hand),
+                (poker_type, ranks.to_string()),
+                "{}",
+                hand
+            );
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::bail;
+use anyhow::Result as AnyResult;
+use itertools::Itertools;
+
+use clap::ValueEnum;
+
+pub const CARDS: &str = "AKQJT98765432";
+
+/// Where wildcards rank when hands of the same type are compared card by card
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum WildRank {
+    /// Below every other card
+    Low,
+    /// At their usual place in the card order
+    Natural,
+    /// Above every other card
+    High,
+}
+
+/// The deck a game of Camel Cards is played with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules {
+    /// Card alphabet, strongest first
+    cards: Vec<char>,
+    wild: Vec<char>,
+    wild_rank: WildRank,
+}
+
+impl Rules {
+    pub fn new(cards: &str, wild: &str, wild_rank: WildRank) -> AnyResult<Self> {
+        let cards: Vec<char> = cards.chars().collect();
+        if cards.is_empty() {
+            bail!("The card alphabet is empty");
+        }
+        if let Some(c) = cards.iter().duplicates().next() {
+            bail!("Card `{}` appears more than once in the card alphabet", c);
+        }
+        let wild: Vec<char> = wild.chars().unique().collect();
+        if let Some(c) = wild.iter().find(|c| !cards.contains(c)) {
+            bail!("Wildcard `{}` is not in the card alphabet", c);
+        }
+        Ok(Rules {
+            cards,
+            wild,
+            wild_rank,
+        })
+    }
+
+    /// Card alphabet, strongest first
+    pub fn cards(&self) -> &[char] {
+        &self.cards
+    }
+
+    /// The card with the given rank
+    pub fn card(&self, rank: u32) -> Option<char> {
+        self.cards
+            .iter()
+            .copied()
+            .find(|&c| self.rank(c) == Some(rank))
+    }
+
+    /// Bits needed to hold any card rank
+    pub fn rank_bits(&self) -> u32 {
+        u32::BITS - (2 * self.cards.len() as u32).leading_zeros()
+    }
+
+    /// Ranks of the weakest and the strongest card
+    pub fn rank_bounds(&self) -> (u32, u32) {
+        let ranks = self.cards.iter().filter_map(|&c| self.rank(c));
+        ranks.clone().min().zip(ranks.max()).unwrap()
+    }
+
+    pub fn is_wild(&self, card: char) -> bool {
+        self.wild.contains(&card)
+    }
+
+    /// Tie-breaking rank of a card, higher is stronger. Wildcards keep their
+    /// relative order among themselves.
+    pub fn rank(&self, card: char) -> Option<u32> {
+        let index = self.cards.iter().position(|&c| c == card)?;
+        let natural = (self.cards.len() - index) as u32;
+        let offset = self.cards.len() as u32;
+        Some(match (self.wild_rank, self.is_wild(card)) {
+            (WildRank::Low, false) | (WildRank::High, true) => natural + offset,
+            _ => natural,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks() {
+        let rules = Rules::new(CARDS, "", WildRank::Low).unwrap();
+        assert!(rules.rank('A') > rules.rank('K'));
+        assert!(rules.rank('J') > rules.rank('T'));
+        assert_eq!(rules.rank('X'), None);
+
+        let rules = Rules::new(CARDS, "J", WildRank::Low).unwrap();
+        assert!(rules.rank('J') < rules.rank('2'));
+
+        let rules = Rules::new(CARDS, "J2", WildRank::High).unwrap();
+        assert!(rules.rank('2') > rules.rank('A'));
+        assert!(rules.rank('J') > rules.rank('2'));
+
+        let rules = Rules::new("321", "2", WildRank::Natural).unwrap();
+        assert!(rules.rank('3') > rules.rank('2'));
+        assert!(rules.rank('2') > rules.rank('1'));
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert!(Rules::new("", "", WildRank::Low).is_err());
+        assert!(Rules::new("AKA", "", WildRank::Low).is_err());
+        assert!(Rules::new(CARDS, "X", WildRank::Low).is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+/// Sizes of the groups of equal cards in a hand, largest first. `[3, 1, 1]`
+/// is three of a kind.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Signature(Vec<u32>);
+
+impl Signature {
+    pub fn new(mut counts: Vec<u32>) -> Self {
+        counts.retain(|&c| c > 0);
+        counts.sort_by(|a, b| b.cmp(a));
+        Signature(counts)
+    }
+
+    /// Signature of the given cards
+    pub fn of<T: Eq + std::hash::Hash>(cards: impl IntoIterator<Item = T>) -> Self {
+        Signature::new(
+            cards
+                .into_iter()
+                .counts()
+                .into_values()
+                .map(|c| c as u32)
+                .collect(),
+        )
+    }
+
+    pub fn counts(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// The usual name of five-card signatures
+    pub fn name(&self) -> Option<&'static str> {
+        match self.0[..] {
+            [5] => Some("five of a kind"),
+            [4, 1] => Some("four of a kind"),
+            [3, 2] => Some("full house"),
+            [3, 1, 1] => Some("three of a kind"),
+            [2, 2, 1] => Some("two pair"),
+            [2, 1, 1, 1] => Some("one pair"),
+            [1, 1, 1, 1, 1] => Some("high card"),
+            _ => None,
+        }
+    }
+
+    /// Number of cards in the hand
+    pub fn size(&self) -> u32 {
+        self.0.iter().sum()
+    }
+
+    /// Signatures reachable by adding one more card to this hand, either to one
+    /// of its groups or as a new group
+    fn grow(&self) -> Vec<Signature> {
+        let mut result: Vec<Signature> = self
+            .0
+            .iter()
+            .enumerate()
+            .dedup_by(|a, b| a.1 == b.1)
+            .map(|(i, _)| {
+                let mut counts = self.0.clone();
+                counts[i] += 1;
+                Signature::new(counts)
+            })
+            .collect();
+        let mut counts = self.0.clone();
+        counts.push(1);
+        result.push(Signature::new(counts));
+        result
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().join("+"))
+    }
+}
+
+impl FromStr for Signature {
+    type Err = String;
+
+    /// Accepts `3+1+1`, or `311` when every group is smaller than ten. A lone
+    /// group of ten or more, e.g. `10`, needs no `+` as long as it cannot be
+    /// read as several groups: `101` could be `10+1` and is rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let counts: Vec<u32> = if s.contains('+') {
+            s.split('+')
+                .map(|c| c.trim().parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|_| format!("Invalid signature `{}`", s))?
+        } else {
+            let digits: Vec<u32> = s.chars().filter_map(|c| c.to_digit(10)).collect();
+            if digits.len() != s.len() || digits.is_empty() {
+                return Err(format!("Invalid signature `{}`", s));
+            }
+            match digits.contains(&0) {
+                // a single group of ten or more cards
+                true if digits[1..].iter().all(|&d| d == 0) => vec![s
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid signature `{}`", s))?],
+                true => {
+                    return Err(format!(
+                        "Signature `{}` is ambiguous, separate its groups with `+`",
+                        s
+                    ))
+                }
+                false => digits,
+            }
+        };
+        if counts.is_empty() || counts.contains(&0) {
+            return Err(format!("Invalid signature `{}`", s));
+        }
+        let signature = Signature::new(counts.clone());
+        if signature.0 != counts {
+            return Err(format!("Signature `{}` is not sorted largest first", s));
+        }
+        Ok(signature)
+    }
+}
+
+/// All signatures of hands with `n` cards, strongest first under the default
+/// ranking
+pub fn partitions(n: u32) -> Vec<Signature> {
+    fn build(remaining: u32, max: u32, prefix: &mut Vec<u32>, out: &mut Vec<Signature>) {
+        if remaining == 0 {
+            out.push(Signature(prefix.clone()));
+            return;
+        }
+        for part in (1..=remaining.min(max)).rev() {
+            prefix.push(part);
+            build(remaining - part, part, prefix, out);
+            prefix.pop();
+        }
+    }
+    let mut out = vec![];
+    build(n, n, &mut vec![], &mut out);
+    out
+}
+
+/// Order of hand types, deciding which signatures beat which
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ranking {
+    /// Compare the largest group first, then the next one, and so on. For
+    /// five cards this is the usual Camel Cards order.
+    Lexicographic,
+    /// Explicit list, strongest first. Signatures not in the list are invalid.
+    Custom(Vec<Signature>),
+}
+
+impl FromStr for Ranking {
+    type Err = String;
+
+    /// `lexicographic`, or a comma separated list of signatures, strongest
+    /// first, e.g. `5,41,32,311,221,2111,11111`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "lexicographic" {
+            return Ok(Ranking::Lexicographic);
+        }
+        let signatures = s
+            .split(',')
+            .map(|sig| sig.parse::<Signature>())
+            .collect::<Result<Vec<Signature>, String>>()?;
+        if let Some(sig) = signatures.iter().duplicates().next() {
+            return Err(format!("Signature `{}` is ranked more than once", sig));
+        }
+        Ok(Ranking::Custom(signatures))
+    }
+}
+
+/// Strength of `signature` in `order`, strongest first
+fn strength_in(order: &[Signature], signature: &Signature) -> Option<u32> {
+    let index = order.iter().position(|s| s == signature)?;
+    Some((order.len() - index) as u32)
+}
+
+impl Ranking {
+    /// The signatures of hands with `size` cards this ranking covers,
+    /// strongest first
+    pub fn order(&self, size: u32) -> Vec<Signature> {
+        match self {
+            Ranking::Lexicographic => partitions(size),
+            Ranking::Custom(order) => order.iter().filter(|s| s.size() == size).cloned().collect(),
+        }
+    }
+
+    /// Strength of a signature among those of the same size, higher is
+    /// stronger. `None` if the ranking does not include the signature.
+    pub fn strength(&self, signature: &Signature) -> Option<u32> {
+        strength_in(&self.order(signature.size()), signature)
+    }
+
+    /// Best signature a hand can reach when each of its `wild` wildcards may
+    /// stand for any card. `natural` is the signature of the other cards.
+    pub fn best_with_wildcards(&self, natural: &Signature, wild: u32) -> Option<Signature> {
+        if let Ranking::Lexicographic = self {
+            // piling every wildcard onto the largest group is always best
+            let mut counts = natural.0.clone();
+            match counts.first_mut() {
+                Some(largest) => *largest += wild,
+                None if wild > 0 => counts.push(wild),
+                None => {}
+            }
+            return Some(Signature::new(counts));
+        }
+
+        let mut reachable: HashSet<Signature> = HashSet::from([natural.clone()]);
+        for _ in 0..wild {
+            reachable = reachable.iter().flat_map(|s| s.grow()).collect();
+        }
+        let order = self.order(natural.size() + wild);
+        reachable
+            .into_iter()
+            .filter_map(|s| Some((strength_in(&order, &s)?, s)))
+            .max()
+            .map(|(_, s)| s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandType {
+    pub signature: Signature,
+    /// Position in the ranking, higher is stronger
+    pub strength: u32,
+}
+
+/// Classifies hands under a ranking, remembering the result for every
+/// combination of natural signature and wildcard count it has seen, and the
+/// order of the signatures of every hand size
+pub struct Classifier<'a> {
+    ranking: &'a Ranking,
+    cache: HashMap<(Signature, u32), Option<HandType>>,
+    orders: HashMap<u32, Vec<Signature>>,
+}
+
+impl<'a> Classifier<'a> {
+    pub fn new(ranking: &'a Ranking) -> Self {
+        Classifier {
+            ranking,
+            cache: HashMap::new(),
+            orders: HashMap::new(),
+        }
+    }
+
+    /// Best hand type reachable with `wild` wildcards on top of `natural`,
+    /// `None` if the ranking does not cover it
+    pub fn classify(&mut self, natural: Signature, wild: u32) -> Option<HandType> {
+        let ranking = self.ranking;
+        let orders = &mut self.orders;
+        self.cache
+            .entry((natural, wild))
+            .or_insert_with_key(|(natural, wild)| {
+                let signature = ranking.best_with_wildcards(natural, *wild)?;
+                let order = orders
+                    .entry(signature.size())
+                    .or_insert_with(|| ranking.order(signature.size()));
+                let strength = strength_in(order, &signature)?;
+                Some(HandType {
+                    signature,
+                    strength,
+                })
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_counts() {
+        let counts: Vec<usize> = (1..=10).map(|n| partitions(n).len()).collect();
+        assert_eq!(counts, vec![1, 2, 3, 5, 7, 11, 15, 22, 30, 42]);
+        assert_eq!(
+            partitions(5)
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>(),
+            vec!["5", "4+1", "3+2", "3+1+1", "2+2+1", "2+1+1+1", "1+1+1+1+1"]
+        );
+    }
+
+    #[test]
+    fn signature_of_every_partition() {
+        let alphabet: Vec<char> = "ABCDEFGHIJ".chars().collect();
+        for n in 1..=10 {
+            for partition in partitions(n) {
+                let cards: Vec<char> = partition
+                    .0
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, &c)| std::iter::repeat_n(alphabet[i], c as usize))
+                    .collect();
+                assert_eq!(Signature::of(cards.iter()), partition);
+                assert_eq!(Signature::of(cards.iter().rev()), partition);
+                assert_eq!(partition.to_string().parse::<Signature>(), Ok(partition));
+            }
+        }
+    }
+
+    #[test]
+    fn lexicographic_strength_follows_partition_order() {
+        for n in 1..=10 {
+            let strengths: Vec<u32> = partitions(n)
+                .iter()
+                .map(|p| Ranking::Lexicographic.strength(p).unwrap())
+                .collect();
+            assert!(strengths.windows(2).all(|w| w[0] > w[1]));
+        }
+    }
+
+    #[test]
+    fn wildcards_match_exhaustive_search() {
+        for n in 1..=8 {
+            let custom = Ranking::Custom(partitions(n));
+            for partition in partitions(n) {
+                // turn every possible number of cards into wildcards, taking
+                // them off the smallest groups first and then off larger ones
+                for wild in 0..=n {
+                    let mut counts = partition.0.to_vec();
+                    let mut left = wild;
+                    while left > 0 {
+                        let last = counts.last_mut().unwrap();
+                        *last -= 1;
+                        if *last == 0 {
+                            counts.pop();
+                        }
+                        left -= 1;
+                    }
+                    let natural = Signature::new(counts);
+                    assert_eq!(
+                        Ranking::Lexicographic.best_with_wildcards(&natural, wild),
+                        custom.best_with_wildcards(&natural, wild),
+                        "{} with {} wildcards",
+                        natural,
+                        wild
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn custom_ranking() {
+        // flushes aside, a ranking where two pair beats three of a kind
+        let ranking: Ranking = "5,41,32,221,311,2111,11111".parse().unwrap();
+        let two_pair: Signature = "221".parse().unwrap();
+        let three: Signature = "3+1+1".parse().unwrap();
+        assert!(ranking.strength(&two_pair) > ranking.strength(&three));
+
+        // with one wildcard and a pair, making two pair beats three of a kind
+        let natural: Signature = "211".parse().unwrap();
+        assert_eq!(ranking.best_with_wildcards(&natural, 1), Some(two_pair));
+        assert_eq!(
+            Ranking::Lexicographic.best_with_wildcards(&natural, 1),
+            Some(three)
+        );
+
+        let partial: Ranking = "5,41".parse().unwrap();
+        assert_eq!(partial.strength(&"32".parse().unwrap()), None);
+        assert_eq!(
+            partial.best_with_wildcards(&"3".parse().unwrap(), 2),
+            Some(Signature(vec![5]))
+        );
+
+        // strengths count among the signatures of the same size
+        let sizes: Ranking = "5,41,3,21,111".parse().unwrap();
+        assert_eq!(sizes.strength(&"5".parse().unwrap()), Some(2));
+        assert_eq!(sizes.strength(&"3".parse().unwrap()), Some(3));
+        assert_eq!(sizes.strength(&"111".parse().unwrap()), Some(1));
+
+        assert!("5,41,5".parse::<Ranking>().is_err());
+        assert!("14".parse::<Ranking>().is_err());
+        assert!("3+0".parse::<Ranking>().is_err());
+    }
+
+    #[test]
+    fn large_groups() {
+        assert_eq!("10".parse(), Ok(Signature(vec![10])));
+        assert_eq!("100".parse(), Ok(Signature(vec![100])));
+        assert_eq!("10+1".parse(), Ok(Signature(vec![10, 1])));
+        assert!("101".parse::<Signature>().is_err());
+        assert!("110".parse::<Signature>().is_err());
+    }
+}
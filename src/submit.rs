@@ -0,0 +1,419 @@
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Result as AnyResult;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::all;
+use crate::client::{Client, ClientArgs};
+use crate::day::Day;
+
+/// Wait after a wrong answer when the response does not say how long
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+#[derive(Args, Debug)]
+pub struct CommandSubmitArgs {
+    pub day: Day,
+
+    #[clap(value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: u8,
+
+    /// Answer to submit. Defaults to solving the day on its input.
+    #[clap(long)]
+    pub answer: Option<String>,
+
+    /// Directory holding the inputs, named after their day, e.g. `one.txt`
+    #[clap(long, default_value = "inputs")]
+    pub inputs: String,
+
+    /// File recording the answers submitted so far
+    #[clap(long, default_value = ".aoc-submissions.json")]
+    pub history: String,
+
+    #[clap(flatten)]
+    pub client: ClientArgs,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::Right => "right",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too high",
+            Verdict::TooLow => "too low",
+        })
+    }
+}
+
+/// What the site made of a submission
+#[derive(Debug, PartialEq)]
+pub enum Response {
+    /// The answer was checked, and the next one may only come after the
+    /// cooldown
+    Checked(Verdict, Duration),
+    /// The answer came too soon after the previous one and was not checked
+    Wait(Duration),
+    /// The part is solved already, or its first part is not
+    WrongLevel,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Submission {
+    pub day: u32,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+/// Answers submitted so far, and when the next one is allowed, in seconds
+/// since the epoch
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct History {
+    pub submissions: Vec<Submission>,
+    pub not_before: u64,
+}
+
+/// Text of the page with its tags removed and its whitespace collapsed
+fn page_text(html: &str) -> String {
+    let html = match (html.find("<article"), html.rfind("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Durations written as `1m 30s`, `45s`, `one minute` or `5 minutes`
+fn parse_duration(text: &str) -> Option<Duration> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let [count, unit] = words[..] else {
+        return parse_units(&words);
+    };
+    let count = match count {
+        "one" => 1,
+        "two" => 2,
+        "five" => 5,
+        "ten" => 10,
+        n => match n.parse() {
+            Ok(n) => n,
+            Err(_) => return parse_units(&words),
+        },
+    };
+    match unit.trim_end_matches('s') {
+        "second" => Some(Duration::from_secs(count)),
+        "minute" => Some(Duration::from_secs(60 * count)),
+        "hour" => Some(Duration::from_secs(3600 * count)),
+        _ => None,
+    }
+}
+
+fn parse_units(words: &[&str]) -> Option<Duration> {
+    words.iter().try_fold(Duration::ZERO, |total, word| {
+        let (count, unit) = word.split_at(word.find(|c: char| !c.is_ascii_digit())?);
+        let count: u64 = count.parse().ok()?;
+        let seconds = match unit {
+            "s" => count,
+            "m" => 60 * count,
+            "h" => 3600 * count,
+            _ => return None,
+        };
+        Some(total + Duration::from_secs(seconds))
+    })
+}
+
+/// The text between the first `after` and the last `before` ahead of it
+fn between<'a>(text: &'a str, before: &str, after: &str) -> Option<&'a str> {
+    let end = text.find(after)?;
+    let start = text[..end].rfind(before)? + before.len();
+    Some(&text[start..end])
+}
+
+pub fn parse_response(html: &str) -> AnyResult<Response> {
+    let text = page_text(html);
+    let lower = text.to_lowercase();
+    if lower.contains("that's the right answer") {
+        return Ok(Response::Checked(Verdict::Right, Duration::ZERO));
+    }
+    if lower.contains("that's not the right answer") {
+        let verdict = if lower.contains("answer is too high") {
+            Verdict::TooHigh
+        } else if lower.contains("answer is too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        };
+        let cooldown = between(&lower, "please wait ", " before trying again")
+            .and_then(parse_duration)
+            .unwrap_or(DEFAULT_COOLDOWN);
+        return Ok(Response::Checked(verdict, cooldown));
+    }
+    if lower.contains("you gave an answer too recently") {
+        let wait = between(&lower, "you have ", " left to wait")
+            .and_then(parse_duration)
+            .unwrap_or(DEFAULT_COOLDOWN);
+        return Ok(Response::Wait(wait));
+    }
+    if lower.contains("you don't seem to be solving the right level") {
+        return Ok(Response::WrongLevel);
+    }
+    bail!("Unexpected response: {}", text)
+}
+
+impl History {
+    pub fn load(file: &str) -> AnyResult<Self> {
+        match fs::read_to_string(file) {
+            Ok(text) => {
+                serde_json::from_str(&text).map_err(|e| anyhow!("Could not read {}: {}", file, e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(History::default()),
+            Err(e) => Err(anyhow!("Could not read {}: {}", file, e)),
+        }
+    }
+
+    pub fn save(&self, file: &str) -> AnyResult<()> {
+        fs::write(file, serde_json::to_string_pretty(self)?)
+            .map_err(|e| anyhow!("Could not write {}: {}", file, e))
+    }
+
+    /// Why `answer` is known to be wrong, or the part already solved,
+    /// without asking the site
+    fn refusal(&self, day: Day, part: u8, answer: &str) -> Option<String> {
+        let previous = self
+            .submissions
+            .iter()
+            .filter(|s| s.day == day.number() && s.part == part);
+        let number = answer.parse::<i128>().ok();
+        for submission in previous {
+            let other = submission.answer.parse::<i128>().ok();
+            match submission.verdict {
+                Verdict::Right => {
+                    return Some(format!(
+                        "Part {} of day {} is solved already: {}",
+                        part,
+                        day.name(),
+                        submission.answer
+                    ))
+                }
+                _ if submission.answer == answer => {
+                    return Some(format!(
+                        "{} was submitted already and is {}",
+                        answer, submission.verdict
+                    ))
+                }
+                Verdict::TooHigh if number >= other && other.is_some() => {
+                    return Some(format!(
+                        "{} is too high, since {} was",
+                        answer, submission.answer
+                    ))
+                }
+                Verdict::TooLow if number <= other && number.is_some() => {
+                    return Some(format!(
+                        "{} is too low, since {} was",
+                        answer, submission.answer
+                    ))
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Submits `answer` unless it is known to be wrong or the cooldown is not
+    /// over, recording the verdict. `now` is in seconds since the epoch.
+    pub fn submit(
+        &mut self,
+        client: &Client,
+        day: Day,
+        part: u8,
+        answer: &str,
+        now: u64,
+    ) -> AnyResult<Verdict> {
+        if let Some(refusal) = self.refusal(day, part, answer) {
+            bail!(refusal);
+        }
+        if now < self.not_before {
+            bail!("Wait {}s before submitting again", self.not_before - now);
+        }
+        let path = format!("/day/{}/answer", day.number());
+        let level = part.to_string();
+        let page = client.post(&path, &[("level", &level), ("answer", answer)])?;
+        match parse_response(&page)? {
+            Response::Checked(verdict, cooldown) => {
+                self.not_before = now + cooldown.as_secs();
+                self.submissions.push(Submission {
+                    day: day.number(),
+                    part,
+                    answer: answer.to_string(),
+                    verdict,
+                });
+                Ok(verdict)
+            }
+            Response::Wait(wait) => {
+                self.not_before = now + wait.as_secs();
+                bail!("Answered too recently, wait {}s", wait.as_secs())
+            }
+            Response::WrongLevel => bail!(
+                "Part {} of day {} cannot be submitted: solved already, or part 1 is not",
+                part,
+                day.name()
+            ),
+        }
+    }
+}
+
+pub fn run(args: &CommandSubmitArgs) -> AnyResult<Verdict> {
+    let answer = match &args.answer {
+        Some(answer) => answer.clone(),
+        None => all::answer(&args.inputs, &args.day.name(), args.part)?,
+    };
+    let mut history = History::load(&args.history)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let client = args.client.client()?;
+    let verdict = history.submit(&client, args.day, args.part, &answer, now);
+    // the cooldown counts even when the answer was not checked
+    history.save(&args.history)?;
+    let verdict = verdict?;
+    println!("{} is {}", answer, verdict);
+    Ok(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::client::stub;
+
+    const TOO_HIGH: &str = "<main><article><p>That's not the right answer; your \
+        answer is too high.  If you're stuck, ... <span>(You guessed \
+        <span style=\"white-space:nowrap;\"><code>100</code>.)</span></span> \
+        Please wait one minute before trying again.</p></article></main>";
+    const TOO_RECENT: &str = "<article><p>You gave an answer too recently; you \
+        have to wait after submitting an answer before trying again.  You have \
+        1m 5s left to wait.</p></article>";
+    const RIGHT: &str = "<article><p>That's the right answer!  You are one gold \
+        star closer to restoring snow operations.</p></article>";
+
+    #[test]
+    fn responses() {
+        assert_eq!(
+            parse_response(TOO_HIGH).unwrap(),
+            Response::Checked(Verdict::TooHigh, Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_response(TOO_RECENT).unwrap(),
+            Response::Wait(Duration::from_secs(65))
+        );
+        assert_eq!(
+            parse_response(RIGHT).unwrap(),
+            Response::Checked(Verdict::Right, Duration::ZERO)
+        );
+        let wrong = "<article><p>That's not the right answer.  Please wait 5 \
+            minutes before trying again.</p></article>";
+        assert_eq!(
+            parse_response(wrong).unwrap(),
+            Response::Checked(Verdict::Wrong, Duration::from_secs(300))
+        );
+        let level = "<article><p>You don't seem to be solving the right level.  \
+            Did you already complete it?</p></article>";
+        assert_eq!(parse_response(level).unwrap(), Response::WrongLevel);
+        assert!(parse_response("<html>Log in</html>").is_err());
+    }
+
+    #[test]
+    fn submissions() {
+        let (url, server) = stub::serve(vec![(200, TOO_HIGH), (200, TOO_RECENT), (200, RIGHT)]);
+        let client = Client::new(&url, "secret");
+        let mut history = History::default();
+        let mut submit = |answer: &str, now: u64| {
+            history
+                .submit(&client, Day::Five, 1, answer, now)
+                .map_err(|e| e.to_string())
+        };
+
+        assert_eq!(submit("100", 1000), Ok(Verdict::TooHigh));
+        // refused without asking the site
+        assert_eq!(
+            submit("100", 2000),
+            Err("100 was submitted already and is too high".to_string())
+        );
+        assert_eq!(
+            submit("150", 2000),
+            Err("150 is too high, since 100 was".to_string())
+        );
+        assert_eq!(
+            submit("50", 1030),
+            Err("Wait 30s before submitting again".to_string())
+        );
+        // the site still wants us to wait
+        assert_eq!(
+            submit("50", 1060),
+            Err("Answered too recently, wait 65s".to_string())
+        );
+        assert_eq!(
+            submit("50", 1100),
+            Err("Wait 25s before submitting again".to_string())
+        );
+        assert_eq!(submit("50", 1125), Ok(Verdict::Right));
+        assert_eq!(
+            submit("51", 2000),
+            Err("Part 1 of day five is solved already: 50".to_string())
+        );
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].line.trim(), "POST /day/5/answer HTTP/1.1");
+        assert_eq!(requests[0].body, "level=1&answer=100");
+        assert_eq!(requests[2].body, "level=1&answer=50");
+        assert_eq!(history.submissions.len(), 2);
+    }
+
+    #[test]
+    fn history_round_trip() {
+        let file = std::env::temp_dir().join(format!("aoc-history-{}.json", std::process::id()));
+        let file = file.to_string_lossy().to_string();
+        let history = History {
+            submissions: vec![Submission {
+                day: 7,
+                part: 2,
+                answer: "5905".to_string(),
+                verdict: Verdict::TooLow,
+            }],
+            not_before: 42,
+        };
+        history.save(&file).unwrap();
+        let loaded = History::load(&file).unwrap();
+        assert_eq!(loaded.submissions, history.submissions);
+        assert_eq!(loaded.not_before, 42);
+        assert!(fs::read_to_string(&file).unwrap().contains("\"too_low\""));
+        fs::remove_file(&file).unwrap();
+        assert!(History::load(&file).unwrap().submissions.is_empty());
+
+        // a history that cannot be read is not an empty one
+        let dir = std::env::temp_dir().to_string_lossy().to_string();
+        assert!(History::load(&dir).is_err());
+    }
+}
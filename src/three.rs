@@ -1,41 +1,60 @@
+use anyhow::anyhow;
+use anyhow::Result as AnyResult;
+
 use std::{
     fs::File,
     io::{self, BufRead},
+    num::ParseIntError,
 };
 
 use clap::Args;
+use thiserror::Error;
+
+use crate::part::{Answers, Part};
 
 #[derive(Args, Debug)]
 pub struct CommandThreeArgs {
-    file: String,
+    pub file: String,
 
-    #[clap(long, short = '2', action)]
-    two: bool,
+    /// Parts to solve
+    #[clap(long, value_enum, default_value_t = Part::Both)]
+    pub part: Part,
 }
 
 #[derive(PartialEq, Debug)]
-struct TokenPosition {
-    line_number: i32,
-    start: i32,
-    end: i32,
+pub struct TokenPosition {
+    pub line_number: i32,
+    pub start: i32,
+    pub end: i32,
 }
 
 impl TokenPosition {
-    fn intersect(&self, other: &Self) -> bool {
+    pub fn intersect(&self, other: &Self) -> bool {
         if (self.line_number - other.line_number).abs() > 1 {
             return false;
         }
-        return other.start <= self.end + 1 && other.end >= self.start - 1;
+        other.start <= self.end + 1 && other.end >= self.start - 1
     }
 }
 
 #[derive(PartialEq, Debug)]
-struct Token<T> {
-    value: T,
-    position: TokenPosition,
+pub struct Token<T> {
+    pub value: T,
+    pub position: TokenPosition,
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Invalid number `{number}` on line {line}: {source}")]
+pub struct SchematicError {
+    pub line: usize,
+    pub number: String,
+    pub source: ParseIntError,
 }
 
-fn parse_tokens<I>(lines: I) -> (Vec<Token<u32>>, Vec<Token<char>>)
+/// Numbers and symbols of an engine schematic
+pub type Schematic = (Vec<Token<u32>>, Vec<Token<char>>);
+
+pub fn parse_tokens<I>(lines: I) -> Result<Schematic, SchematicError>
 where
     I: Iterator<Item = String>,
 {
@@ -47,10 +66,16 @@ where
     let mut flush_number_buffer = |line_number: usize,
                                    char_idx: usize,
                                    buffer_start_idx: &mut Option<u32>,
-                                   buffer: &mut String| {
+                                   buffer: &mut String|
+     -> Result<(), SchematicError> {
         if let Some(start_idx) = *buffer_start_idx {
+            let value = buffer.parse::<u32>().map_err(|source| SchematicError {
+                line: line_number + 1,
+                number: buffer.clone(),
+                source,
+            })?;
             numbers.push(Token::<u32> {
-                value: buffer.parse::<u32>().unwrap(),
+                value,
                 position: TokenPosition {
                     line_number: line_number as i32,
                     start: start_idx as i32,
@@ -60,22 +85,20 @@ where
             *buffer_start_idx = None;
             buffer.clear();
         }
+        Ok(())
     };
 
     for (line_number, line) in lines.enumerate() {
-        let mut last_char_idx = 0;
         for (char_idx, ch) in line.chars().enumerate() {
-            last_char_idx = char_idx;
-
             // if character is a number
             if ch.is_numeric() {
                 buffer.push(ch);
-                if buffer_start_idx == None {
+                if buffer_start_idx.is_none() {
                     buffer_start_idx = Some(char_idx as u32);
                 }
             } else {
                 // close out number parsing if buffer has a value
-                flush_number_buffer(line_number, char_idx, &mut buffer_start_idx, &mut buffer);
+                flush_number_buffer(line_number, char_idx, &mut buffer_start_idx, &mut buffer)?;
 
                 // if character is a symbol
                 if !ch.is_numeric() && ch != '.' {
@@ -90,53 +113,71 @@ where
                 }
             }
         }
+        // a number ending the line ends on its last character
         flush_number_buffer(
             line_number,
-            last_char_idx,
+            line.chars().count(),
             &mut buffer_start_idx,
             &mut buffer,
-        );
+        )?;
     }
-    (numbers, symbols)
+    Ok((numbers, symbols))
 }
 
-pub fn run(args: &CommandThreeArgs) -> u32 {
-    let file = File::open(args.file.as_str()).expect("Should have been able to read the file");
+/// Sum of the numbers next to a symbol
+pub fn part_one(numbers: &[Token<u32>], symbols: &[Token<char>]) -> u64 {
+    numbers
+        .iter()
+        .filter(|n| symbols.iter().any(|s| n.position.intersect(&s.position)))
+        .map(|n| n.value as u64)
+        .sum()
+}
+
+/// Sum of the products of the numbers around each gear
+pub fn part_two(numbers: &[Token<u32>], symbols: &[Token<char>]) -> AnyResult<u64> {
+    let overflow = |star: &Token<char>| {
+        anyhow!(
+            "The gear ratios overflow at line {}, column {}",
+            star.position.line_number + 1,
+            star.position.start + 1
+        )
+    };
+    symbols
+        .iter()
+        .filter(|s| s.value == '*')
+        .try_fold(0u64, |sum, star| {
+            let parts: Vec<&Token<u32>> = numbers
+                .iter()
+                .filter(|n| star.position.intersect(&n.position))
+                .collect::<Vec<&Token<u32>>>();
+
+            // mulitply part values together
+            if parts.len() < 2 {
+                return Ok(sum);
+            }
+            parts
+                .iter()
+                .try_fold(1u64, |ratio, p| ratio.checked_mul(p.value as u64))
+                .and_then(|ratio| sum.checked_add(ratio))
+                .ok_or_else(|| overflow(star))
+        })
+}
+
+pub fn run(args: &CommandThreeArgs) -> AnyResult<Answers<u64>> {
+    let file =
+        File::open(&args.file).map_err(|e| anyhow!("Could not read {}: {}", args.file, e))?;
     let lines = io::BufReader::new(file)
         .lines()
-        .into_iter()
-        .map(|l| l.unwrap().to_string());
-    let (numbers, symbols) = parse_tokens(lines);
-
-    if !args.two {
-        let sum = numbers
-            .iter()
-            .filter(|n| symbols.iter().any(|s| n.position.intersect(&s.position)))
-            .map(|n| n.value)
-            .sum();
-        println!("The sum is: {}", sum);
-        sum
-    } else {
-        let sum = symbols
-            .iter()
-            .filter(|s| s.value == '*')
-            .map(|star| {
-                let parts: Vec<&Token<u32>> = numbers
-                    .iter()
-                    .filter(|n| star.position.intersect(&n.position))
-                    .collect::<Vec<&Token<u32>>>()
-                    .into();
-
-                // mulitply part values together
-                if parts.len() < 2 {
-                    return 0;
-                }
-                parts.iter().map(|p| p.value).reduce(|a, e| a * e).unwrap() as u32
-            })
-            .sum();
-        println!("The sum is: {}", sum);
-        sum
-    }
+        .collect::<io::Result<Vec<String>>>()?;
+    let (numbers, symbols) = parse_tokens(lines.into_iter())?;
+
+    let answers = Answers::solve(
+        args.part,
+        || Ok(part_one(&numbers, &symbols)),
+        || part_two(&numbers, &symbols),
+    )?;
+    println!("{}", answers);
+    Ok(answers)
 }
 
 #[cfg(test)]
@@ -148,9 +189,11 @@ mod tests {
         assert_eq!(
             self::run(&CommandThreeArgs {
                 file: "./inputs/three_test.txt".to_string(),
-                two: false
-            }),
-            4361,
+                part: Part::One
+            })
+            .unwrap()
+            .one,
+            Some(4361),
         );
     }
 
@@ -159,15 +202,35 @@ mod tests {
         assert_eq!(
             self::run(&CommandThreeArgs {
                 file: "./inputs/three_test.txt".to_string(),
-                two: true
-            }),
-            467835,
+                part: Part::Two
+            })
+            .unwrap()
+            .two,
+            Some(467835),
+        );
+    }
+
+    #[test]
+    fn large_gear_ratios() {
+        let lines = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        let schematic = lines(&["999999*999999"]);
+        let (numbers, symbols) = parse_tokens(schematic.into_iter()).unwrap();
+        assert_eq!(part_one(&numbers, &symbols), 2 * 999999);
+        assert_eq!(part_two(&numbers, &symbols).unwrap(), 999999 * 999999);
+
+        // four numbers around one gear
+        let schematic = lines(&["99999.99999", ".....*.....", "99999.99999"]);
+        let (numbers, symbols) = parse_tokens(schematic.into_iter()).unwrap();
+        let error = part_two(&numbers, &symbols).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The gear ratios overflow at line 2, column 6"
         );
     }
 
     #[test]
     fn test_parse_tokens() {
-        let (numbers, symbols) = parse_tokens([String::from("1234...*..!")].into_iter());
+        let (numbers, symbols) = parse_tokens([String::from("1234...*..!")].into_iter()).unwrap();
         assert_eq!(numbers.len(), 1);
         assert_eq!(
             numbers.first(),
@@ -204,4 +267,16 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let (numbers, _) =
+            parse_tokens([String::from("5"), String::from("..12")].into_iter()).unwrap();
+        assert_eq!(numbers[0].position.end, 0);
+        assert_eq!(numbers[1].position.end, 3);
+
+        let error = parse_tokens([String::from("..99999999999")].into_iter()).unwrap_err();
+        assert_eq!(error.number, "99999999999");
+        assert!(parse_tokens([String::from("1٣")].into_iter()).is_err());
+    }
 }
@@ -1,40 +1,118 @@
+use anyhow::anyhow;
+use anyhow::Result as AnyResult;
+
 use std::{
     cmp::Ordering,
-    error::Error,
+    fmt,
     fs::File,
     io::{self, BufRead},
+    num::ParseIntError,
+    str::FromStr,
 };
 
 use clap::Args;
+use itertools::Itertools;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::part::{Answers, Part};
+
+pub mod pareto;
+pub mod report;
+
+use report::{Report, ReportFormat};
 
 #[derive(Args, Debug)]
 pub struct CommandTwoArgs {
-    file: String,
+    pub file: String,
+
+    /// Parts to solve
+    #[clap(long, value_enum, default_value_t = Part::Both)]
+    pub part: Part,
+
+    /// Bag to test games against in part one, e.g. `red=12,green=13,blue=14`.
+    /// May be repeated to evaluate several candidate bags in one run.
+    #[clap(long = "bag", value_parser = parse_bag)]
+    pub bags: Vec<GameConfiguration>,
 
-    #[clap(long, short = '2', action)]
-    two: bool,
+    /// Report the smallest bag that makes each game possible
+    #[clap(long, action)]
+    pub smallest: bool,
+
+    /// Print the minimal bag report for every game before the answers
+    #[clap(long, action)]
+    pub report: bool,
+
+    /// Format of the `--report` output
+    #[clap(long, value_enum, default_value_t = ReportFormat::Table)]
+    pub format: ReportFormat,
+
+    /// Print the Pareto frontier of all draws and which games one extra cube
+    /// of each color would make possible for the first bag
+    #[clap(long, action)]
+    pub pareto: bool,
 }
 
+// 12 red cubes, 13 green cubes, and 14 blue cubes
+pub const DEFAULT_BAG: GameConfiguration = GameConfiguration {
+    red: 12,
+    green: 13,
+    blue: 14,
+};
+
 #[derive(Debug, PartialEq)]
-struct Game {
-    id: u32,
-    configurations: Vec<GameConfiguration>,
+pub struct Game {
+    pub id: u32,
+    pub configurations: Vec<GameConfiguration>,
 }
 
-impl Game {
-    pub fn from_str(value: String) -> Option<Self> {
-        let split: Vec<&str> = value.split(":").collect();
-        let game_id = split[0][5..].parse::<u32>().unwrap();
-        Some(Game {
-            id: game_id,
-            configurations: split[1]
-                .split(";")
-                .map(|g| GameConfiguration::from_str(g.trim().to_string()).unwrap())
-                .collect(),
+#[derive(Error, Debug, PartialEq)]
+pub enum GameParseError {
+    #[error("Expected `Game <id>: <draws>`, got `{0}`")]
+    FormatError(String),
+
+    #[error("Invalid game id in `{line}`: {source}")]
+    InvalidId { line: String, source: ParseIntError },
+
+    #[error("Invalid count in `{draw}`: {source}")]
+    InvalidCount { draw: String, source: ParseIntError },
+
+    #[error("Could not parse {0}")]
+    InvalidDraw(String),
+}
+
+impl FromStr for Game {
+    type Err = GameParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, draws) = value
+            .split_once(':')
+            .ok_or_else(|| GameParseError::FormatError(value.to_string()))?;
+        let id = name
+            .strip_prefix("Game ")
+            .ok_or_else(|| GameParseError::FormatError(value.to_string()))?;
+        let id = id
+            .parse::<u32>()
+            .map_err(|source| GameParseError::InvalidId {
+                line: value.to_string(),
+                source,
+            })?;
+        Ok(Game {
+            id,
+            configurations: draws
+                .split(';')
+                .map(|g| GameConfiguration::from_str(g.trim()))
+                .collect::<Result<_, _>>()?,
         })
     }
+}
 
-    fn min(&self) -> GameConfiguration {
+impl Game {
+    pub fn is_possible(&self, bag: &GameConfiguration) -> bool {
+        self.configurations.iter().all(|c| c <= bag)
+    }
+
+    pub fn min(&self) -> GameConfiguration {
         GameConfiguration {
             red: self.configurations.iter().map(|c| c.red).max().unwrap(),
             green: self.configurations.iter().map(|c| c.green).max().unwrap(),
@@ -43,11 +121,11 @@ impl Game {
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct GameConfiguration {
-    red: u32,
-    green: u32,
-    blue: u32,
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameConfiguration {
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
 }
 
 impl PartialOrd for GameConfiguration {
@@ -65,19 +143,30 @@ impl PartialOrd for GameConfiguration {
     }
 }
 
-impl GameConfiguration {
-    pub fn from_str(value: String) -> Result<Self, Box<dyn Error>> {
+impl FromStr for GameConfiguration {
+    type Err = GameParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
         let mut r = 0u32;
         let mut g = 0u32;
         let mut b = 0u32;
         let configurations = value.trim().split(",").map(|s| s.trim().to_lowercase());
         for c in configurations {
             let vals: Vec<&str> = c.split(" ").collect();
-            match vals[..] {
-                [count, "red"] => r = count.parse::<u32>().unwrap(),
-                [count, "green"] => g = count.parse::<u32>().unwrap(),
-                [count, "blue"] => b = count.parse::<u32>().unwrap(),
-                _ => return Err(format!("Could not parse {}", value).into()),
+            let (count, color) = match vals[..] {
+                [count, color @ ("red" | "green" | "blue")] => (count, color),
+                _ => return Err(GameParseError::InvalidDraw(value.to_string())),
+            };
+            let count = count
+                .parse::<u32>()
+                .map_err(|source| GameParseError::InvalidCount {
+                    draw: c.clone(),
+                    source,
+                })?;
+            match color {
+                "red" => r = count,
+                "green" => g = count,
+                _ => b = count,
             }
         }
         Ok(Self {
@@ -86,49 +175,163 @@ impl GameConfiguration {
             blue: b,
         })
     }
+}
 
-    fn power(&self) -> u32 {
-        self.red * self.green * self.blue
+impl GameConfiguration {
+    pub fn power(&self) -> AnyResult<u64> {
+        (self.red as u64)
+            .checked_mul(self.green as u64)
+            .and_then(|p| p.checked_mul(self.blue as u64))
+            .ok_or_else(|| anyhow!("The power of bag {} overflows", self))
     }
 }
 
-pub fn run(args: &CommandTwoArgs) -> u32 {
-    let file = File::open(args.file.as_str()).expect("Should have been able to read the file");
-    let lines = io::BufReader::new(file).lines();
+impl fmt::Display for GameConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "red={},green={},blue={}",
+            self.red, self.green, self.blue
+        )
+    }
+}
 
-    if !args.two {
-        // 12 red cubes, 13 green cubes, and 14
-        let test_configuration = GameConfiguration {
-            red: 12,
-            green: 13,
-            blue: 14,
-        };
-        let sum: u32 = lines
-            .into_iter()
-            .map(|line| Game::from_str(line.unwrap()).unwrap())
-            .filter(|g| g.configurations.iter().all(|c| c <= &test_configuration))
-            .map(|g| g.id)
-            .sum();
-        println!("The sum is: {}", sum);
-        sum
-    } else {
-        let sum: u32 = lines
-            .into_iter()
-            .map(|line| Game::from_str(line.unwrap()).unwrap().min().power())
-            .sum();
-        println!("The sum is: {}", sum);
-        sum
+/// Parses a bag given as `red=12,green=13,blue=14`. Colors that are left out
+/// hold no cubes.
+pub fn parse_bag(value: &str) -> Result<GameConfiguration, String> {
+    let mut bag = GameConfiguration {
+        red: 0,
+        green: 0,
+        blue: 0,
+    };
+    for entry in value.split(',').map(|s| s.trim()) {
+        let (color, count) = entry
+            .split_once('=')
+            .ok_or(format!("Expected `color=count`, got `{}`", entry))?;
+        let count = count
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid count in `{}`: {}", entry, e))?;
+        match color.trim().to_lowercase().as_str() {
+            "red" => bag.red = count,
+            "green" => bag.green = count,
+            "blue" => bag.blue = count,
+            other => return Err(format!("Unknown color `{}`", other)),
+        }
+    }
+    Ok(bag)
+}
+
+fn print_pareto(games: &[Game], bag: &GameConfiguration) {
+    let frontier = pareto::frontier(games);
+    println!("Pareto frontier ({} draws):", frontier.len());
+    for draw in frontier.iter() {
+        println!(
+            "  {} (game {}, draw {})",
+            draw.configuration, draw.game, draw.index
+        );
+    }
+    println!("Adding one cube to bag {}:", bag);
+    for (color, ids) in pareto::unlocked_by_one_cube(games, bag) {
+        if ids.is_empty() {
+            println!("  {}: no new games", color);
+        } else {
+            println!("  {}: games {}", color, ids.iter().join(", "));
+        }
+    }
+}
+
+pub fn parse(lines: impl Iterator<Item = String>) -> Result<Vec<Game>, GameParseError> {
+    lines.map(|line| line.parse::<Game>()).collect()
+}
+
+/// Sum of the ids of the games possible with `bag`
+pub fn part_one(games: &[Game], bag: &GameConfiguration) -> u64 {
+    games
+        .iter()
+        .filter(|g| g.is_possible(bag))
+        .map(|g| g.id as u64)
+        .sum()
+}
+
+/// Sum of the powers of the smallest bag for each game
+pub fn part_two(games: &[Game]) -> AnyResult<u64> {
+    games.iter().try_fold(0u64, |sum, g| {
+        sum.checked_add(g.min().power()?)
+            .ok_or_else(|| anyhow!("The sum of the powers overflows at game {}", g.id))
+    })
+}
+
+pub fn run(args: &CommandTwoArgs) -> AnyResult<Answers<u64>> {
+    let file =
+        File::open(&args.file).map_err(|e| anyhow!("Could not read {}: {}", args.file, e))?;
+    let lines = io::BufReader::new(file)
+        .lines()
+        .collect::<io::Result<Vec<String>>>()?;
+    let games = parse(lines.into_iter())?;
+
+    if args.report {
+        println!("{}", Report::new(&games)?.render(args.format));
+    }
+    if args.smallest {
+        for game in games.iter() {
+            println!("Game {}: {}", game.id, game.min());
+        }
+    }
+    let bags = match args.bags.is_empty() {
+        true => vec![DEFAULT_BAG],
+        false => args.bags.clone(),
+    };
+    if args.pareto {
+        print_pareto(&games, &bags[0]);
+    }
+    // with several candidate bags, the first one gives the answer
+    if bags.len() > 1 {
+        for bag in bags.iter() {
+            println!("Bag {}: the sum is: {}", bag, part_one(&games, bag));
+        }
     }
+
+    let answers = Answers::solve(
+        args.part,
+        || Ok(part_one(&games, &bags[0])),
+        || part_two(&games),
+    )?;
+    println!("{}", answers);
+    Ok(answers)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn game_from_str_errors() {
+        let error = |s: &str| Game::from_str(s).unwrap_err();
+        assert!(matches!(error("Game"), GameParseError::FormatError(_)));
+        assert!(matches!(
+            error("Gam: 1 red"),
+            GameParseError::FormatError(_)
+        ));
+        assert!(matches!(
+            error("Game x: 1 red"),
+            GameParseError::InvalidId { .. }
+        ));
+        assert!(matches!(
+            error("Game 1: 1 pink"),
+            GameParseError::InvalidDraw(_)
+        ));
+        assert!(matches!(
+            error("Game 1: 1 red; -2 blue"),
+            GameParseError::InvalidCount { .. }
+        ));
+        assert!(GameConfiguration::from_str("").is_err());
+    }
+
     #[test]
     fn game_configuration_from_str() {
         assert_eq!(
-            GameConfiguration::from_str("1 blue, 2 green".to_string()).unwrap(),
+            GameConfiguration::from_str("1 blue, 2 green").unwrap(),
             GameConfiguration {
                 red: 0,
                 blue: 1,
@@ -174,11 +377,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_input() {
+        let args = CommandTwoArgs {
+            file: "./inputs/two_test.txt".to_string(),
+            part: Part::Both,
+            bags: vec![],
+            smallest: false,
+            report: false,
+            format: ReportFormat::Table,
+            pareto: false,
+        };
+        assert_eq!(
+            run(&args).unwrap(),
+            Answers {
+                one: Some(8),
+                two: Some(2286)
+            }
+        );
+
+        let args = CommandTwoArgs {
+            bags: vec![parse_bag("red=4,green=3,blue=6").unwrap(), DEFAULT_BAG],
+            ..args
+        };
+        assert_eq!(run(&args).unwrap().one, Some(3));
+
+        // the report comes on top of the parts asked for, with the same bags
+        let args = CommandTwoArgs {
+            part: Part::One,
+            report: true,
+            ..args
+        };
+        assert_eq!(
+            run(&args).unwrap(),
+            Answers {
+                one: Some(3),
+                two: None
+            }
+        );
+    }
+
+    #[test]
+    fn large_powers() {
+        let games =
+            parse(["Game 1: 5000 red, 5000 green, 5000 blue".to_string()].into_iter()).unwrap();
+        assert_eq!(part_two(&games).unwrap(), 125_000_000_000);
+
+        let huge = format!("{0} red, {0} green, {0} blue", u32::MAX);
+        let games = parse([format!("Game 1: {}", huge)].into_iter()).unwrap();
+        assert!(part_two(&games).is_err());
+        let games = parse(
+            [
+                format!("Game 4294967295: {}", huge),
+                format!("Game 4294967295: {}", huge),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+        assert_eq!(part_one(&games, &games[0].min()), 2 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn bag_from_str() {
+        assert_eq!(parse_bag("red=12,green=13,blue=14").unwrap(), DEFAULT_BAG);
+        assert_eq!(
+            parse_bag("blue=2").unwrap(),
+            GameConfiguration {
+                red: 0,
+                green: 0,
+                blue: 2
+            }
+        );
+        assert_eq!(parse_bag(&DEFAULT_BAG.to_string()).unwrap(), DEFAULT_BAG);
+        assert!(parse_bag("red:12").is_err());
+        assert!(parse_bag("purple=1").is_err());
+        assert!(parse_bag("red=x").is_err());
+    }
+
     #[test]
     fn game_from_str() {
         assert_eq!(
-            Game::from_str("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".to_string())
-                .unwrap(),
+            Game::from_str("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap(),
             Game {
                 id: 1,
                 configurations: vec![
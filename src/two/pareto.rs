@@ -0,0 +1,175 @@
+use std::{cmp::Ordering, fmt};
+
+use super::{Game, GameConfiguration};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Red => write!(f, "red"),
+            Color::Green => write!(f, "green"),
+            Color::Blue => write!(f, "blue"),
+        }
+    }
+}
+
+pub const COLORS: [Color; 3] = [Color::Red, Color::Green, Color::Blue];
+
+/// A single draw together with where it came from
+#[derive(Debug, PartialEq)]
+pub struct Draw<'a> {
+    pub game: u32,
+    pub index: usize,
+    pub configuration: &'a GameConfiguration,
+}
+
+impl PartialOrd for Draw<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.configuration.partial_cmp(other.configuration)
+    }
+}
+
+impl GameConfiguration {
+    fn with_extra(&self, color: Color, count: u32) -> Self {
+        let mut bag = self.clone();
+        match color {
+            Color::Red => bag.red += count,
+            Color::Green => bag.green += count,
+            Color::Blue => bag.blue += count,
+        }
+        bag
+    }
+}
+
+pub fn draws(games: &[Game]) -> Vec<Draw<'_>> {
+    games
+        .iter()
+        .flat_map(|g| {
+            g.configurations.iter().enumerate().map(move |(i, c)| Draw {
+                game: g.id,
+                index: i + 1,
+                configuration: c,
+            })
+        })
+        .collect()
+}
+
+/// Indices of the elements not strictly dominated by any other element.
+/// Elements that compare equal are only kept once (the first occurrence), so
+/// the result is always an antichain.
+pub fn maximal_indices<T: PartialOrd>(items: &[T]) -> Vec<usize> {
+    (0..items.len())
+        .filter(|&i| {
+            !items.iter().enumerate().any(|(j, other)| {
+                other > &items[i]
+                    || (j < i && other.partial_cmp(&items[i]) == Some(Ordering::Equal))
+            })
+        })
+        .collect()
+}
+
+/// True if no two elements are comparable
+pub fn is_antichain<T: PartialOrd>(items: &[T]) -> bool {
+    items
+        .iter()
+        .enumerate()
+        .all(|(i, a)| items.iter().skip(i + 1).all(|b| a.partial_cmp(b).is_none()))
+}
+
+/// The maximal draws across all games. A bag makes every game possible
+/// exactly when it dominates each draw on the frontier.
+pub fn frontier(games: &[Game]) -> Vec<Draw<'_>> {
+    let draws = draws(games);
+    let keep = maximal_indices(&draws);
+    let frontier: Vec<Draw> = draws
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| keep.contains(i))
+        .map(|(_, d)| d)
+        .collect();
+    debug_assert!(is_antichain(&frontier));
+    frontier
+}
+
+/// For each color, the ids of games that are impossible with `bag` but become
+/// possible once a single cube of that color is added.
+pub fn unlocked_by_one_cube(games: &[Game], bag: &GameConfiguration) -> Vec<(Color, Vec<u32>)> {
+    COLORS
+        .iter()
+        .map(|&color| {
+            let bigger = bag.with_extra(color, 1);
+            let ids = games
+                .iter()
+                .filter(|g| !g.is_possible(bag) && g.is_possible(&bigger))
+                .map(|g| g.id)
+                .collect();
+            (color, ids)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Game> {
+        [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+            "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+            "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        ]
+        .iter()
+        .map(|l| l.parse::<Game>().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn maximal_elements() {
+        let games = sample();
+        let frontier = frontier(&games);
+        let located: Vec<(u32, usize)> = frontier.iter().map(|d| (d.game, d.index)).collect();
+        assert_eq!(located, vec![(3, 1), (3, 2), (4, 3)]);
+
+        assert!(is_antichain(&frontier));
+
+        let all = draws(&games);
+        assert!(all.iter().all(|d| frontier.iter().any(|f| d <= f)));
+    }
+
+    #[test]
+    fn maximal_deduplicates_equal_elements() {
+        let bag = GameConfiguration {
+            red: 1,
+            green: 1,
+            blue: 1,
+        };
+        let items = vec![bag.clone(), bag.clone(), bag.with_extra(Color::Red, 0)];
+        assert_eq!(maximal_indices(&items), vec![0]);
+    }
+
+    #[test]
+    fn one_more_cube() {
+        let games = sample();
+        let bag = GameConfiguration {
+            red: 13,
+            green: 13,
+            blue: 15,
+        };
+        assert_eq!(
+            unlocked_by_one_cube(&games, &bag),
+            vec![
+                (Color::Red, vec![4]),
+                (Color::Green, vec![]),
+                (Color::Blue, vec![])
+            ]
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::anyhow;
+use anyhow::Result as AnyResult;
+
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use super::{Game, GameConfiguration};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Table,
+    Json,
+}
+
+/// 1-based index of the draw that first reaches each color's maximum, or
+/// `None` when the color never shows up in the game.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ForcingDraws {
+    red: Option<usize>,
+    green: Option<usize>,
+    blue: Option<usize>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct GameReport {
+    id: u32,
+    minimal_bag: GameConfiguration,
+    power: u64,
+    forced_by: ForcingDraws,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Summary {
+    games: usize,
+    total_power: u64,
+    min_power: u64,
+    max_power: u64,
+    mean_power: f64,
+    median_power: f64,
+    /// power -> number of games with that power
+    power_distribution: BTreeMap<u64, usize>,
+    /// smallest bag that makes every game possible
+    dominating_bag: GameConfiguration,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Report {
+    games: Vec<GameReport>,
+    summary: Summary,
+}
+
+fn forcing_draw<F>(game: &Game, color: F) -> Option<usize>
+where
+    F: Fn(&GameConfiguration) -> u32,
+{
+    let max = game.configurations.iter().map(&color).max()?;
+    if max == 0 {
+        return None;
+    }
+    game.configurations
+        .iter()
+        .position(|c| color(c) == max)
+        .map(|i| i + 1)
+}
+
+impl GameReport {
+    fn new(game: &Game) -> AnyResult<Self> {
+        let minimal_bag = game.min();
+        Ok(GameReport {
+            id: game.id,
+            power: minimal_bag.power()?,
+            minimal_bag,
+            forced_by: ForcingDraws {
+                red: forcing_draw(game, |c| c.red),
+                green: forcing_draw(game, |c| c.green),
+                blue: forcing_draw(game, |c| c.blue),
+            },
+        })
+    }
+}
+
+impl Summary {
+    fn new(reports: &[GameReport]) -> AnyResult<Self> {
+        let mut powers: Vec<u64> = reports.iter().map(|r| r.power).collect();
+        powers.sort();
+
+        let mut power_distribution = BTreeMap::new();
+        for &power in powers.iter() {
+            *power_distribution.entry(power).or_insert(0) += 1;
+        }
+
+        let total_power = powers
+            .iter()
+            .try_fold(0u64, |sum, &power| sum.checked_add(power))
+            .ok_or_else(|| anyhow!("The total power overflows"))?;
+        let (mean_power, median_power) = match powers.len() {
+            0 => (0.0, 0.0),
+            n if n % 2 == 0 => (
+                total_power as f64 / n as f64,
+                (powers[n / 2 - 1] as f64 + powers[n / 2] as f64) / 2.0,
+            ),
+            n => (total_power as f64 / n as f64, powers[n / 2] as f64),
+        };
+
+        Ok(Summary {
+            games: reports.len(),
+            total_power,
+            min_power: powers.first().copied().unwrap_or(0),
+            max_power: powers.last().copied().unwrap_or(0),
+            mean_power,
+            median_power,
+            power_distribution,
+            dominating_bag: GameConfiguration {
+                red: reports.iter().map(|r| r.minimal_bag.red).max().unwrap_or(0),
+                green: reports
+                    .iter()
+                    .map(|r| r.minimal_bag.green)
+                    .max()
+                    .unwrap_or(0),
+                blue: reports
+                    .iter()
+                    .map(|r| r.minimal_bag.blue)
+                    .max()
+                    .unwrap_or(0),
+            },
+        })
+    }
+}
+
+impl Report {
+    pub fn new(games: &[Game]) -> AnyResult<Self> {
+        let games = games
+            .iter()
+            .map(GameReport::new)
+            .collect::<AnyResult<Vec<GameReport>>>()?;
+        let summary = Summary::new(&games)?;
+        Ok(Report { games, summary })
+    }
+
+    pub fn total_power(&self) -> u64 {
+        self.summary.total_power
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Table => self.to_table(),
+            ReportFormat::Json => serde_json::to_string_pretty(self).unwrap(),
+        }
+    }
+
+    fn to_table(&self) -> String {
+        let draw = |d: Option<usize>| d.map_or("-".to_string(), |d| d.to_string());
+        let mut out = format!(
+            "{:>6} {:>5} {:>5} {:>5} {:>8}  {}\n",
+            "Game", "Red", "Green", "Blue", "Power", "Forced by (r/g/b)"
+        );
+        for r in self.games.iter() {
+            out += &format!(
+                "{:>6} {:>5} {:>5} {:>5} {:>8}  {}/{}/{}\n",
+                r.id,
+                r.minimal_bag.red,
+                r.minimal_bag.green,
+                r.minimal_bag.blue,
+                r.power,
+                draw(r.forced_by.red),
+                draw(r.forced_by.green),
+                draw(r.forced_by.blue),
+            );
+        }
+
+        let s = &self.summary;
+        out += &format!("\nGames: {}\n", s.games);
+        out += &format!("Total power: {}\n", s.total_power);
+        out += &format!(
+            "Power min/median/mean/max: {}/{}/{:.2}/{}\n",
+            s.min_power, s.median_power, s.mean_power, s.max_power
+        );
+        out += &format!("Dominating bag: {}\n", s.dominating_bag);
+        out += "Power distribution:\n";
+        for (power, count) in s.power_distribution.iter() {
+            out += &format!("{:>8}: {}\n", power, count);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    #[test]
+    fn game_report() {
+        let game =
+            Game::from_str("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert_eq!(
+            GameReport::new(&game).unwrap(),
+            GameReport {
+                id: 1,
+                minimal_bag: GameConfiguration {
+                    red: 4,
+                    green: 2,
+                    blue: 6
+                },
+                power: 48,
+                forced_by: ForcingDraws {
+                    red: Some(1),
+                    green: Some(2),
+                    blue: Some(2)
+                },
+            }
+        );
+
+        let game = Game::from_str("Game 2: 3 blue; 1 blue").unwrap();
+        assert_eq!(
+            GameReport::new(&game).unwrap().forced_by,
+            ForcingDraws {
+                red: None,
+                green: None,
+                blue: Some(1)
+            }
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let games: Vec<Game> = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        ]
+        .iter()
+        .map(|l| l.parse::<Game>().unwrap())
+        .collect();
+        let report = Report::new(&games).unwrap();
+        assert_eq!(report.total_power(), 48 + 12 + 1560);
+        assert_eq!(report.summary.median_power, 48.0);
+        assert_eq!(
+            report.summary.dominating_bag,
+            GameConfiguration {
+                red: 20,
+                green: 13,
+                blue: 6
+            }
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Json)).unwrap();
+        assert_eq!(json["games"][2]["minimal_bag"]["red"], 20);
+        assert_eq!(json["summary"]["power_distribution"]["48"], 1);
+    }
+
+    #[test]
+    fn large_powers() {
+        let game = |id: u32, count: u32| {
+            format!("Game {0}: {1} red, {1} green, {1} blue", id, count)
+                .parse::<Game>()
+                .unwrap()
+        };
+        // powers past u32, with a median of two of them
+        let games = [game(1, 5000), game(2, 5000)];
+        let report = Report::new(&games).unwrap();
+        assert_eq!(report.total_power(), 2 * 125_000_000_000);
+        assert_eq!(report.summary.median_power, 125_000_000_000.0);
+
+        let games = [game(1, 2_000_000), game(2, 2_000_000), game(3, 2_000_000)];
+        assert!(Report::new(&games).is_err());
+        assert!(Report::new(&[game(1, u32::MAX)]).is_err());
+    }
+}